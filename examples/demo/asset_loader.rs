@@ -18,8 +18,8 @@ pub struct TextureAssets {
 /// Contain loaded [`Mesh`] assets
 #[derive(Resource, Debug, Default)]
 pub struct MeshAssets {
-    /// Pyramida [`Mesh`]
-    pub pyramida: Option<Handle<Mesh>>,
+    /// Pyramida [`Mesh`], it is rendered once loaded
+    pub pyramida: Handle<Mesh>,
 }
 
 /// Loads assets for shapes [`StandardMaterial`]
@@ -81,7 +81,7 @@ fn load_assets(
     };
 
     *mesh_assets = MeshAssets {
-        pyramida: Some(asset_server.load("scenes\\pyramida.glb#Mesh0/Primitive0")),
+        pyramida: asset_server.load("scenes\\pyramida.glb#Mesh0/Primitive0"),
     };
 }
 
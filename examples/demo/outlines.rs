@@ -1,10 +1,14 @@
 use bevy::input::mouse::MouseWheel;
-use bevy::pbr::{ExtendedMaterial, MaterialExtension};
+use bevy::pbr::MaterialExtension;
 use bevy::prelude::*;
 use bevy::ecs::schedule::SystemSet;
+use bevy::utils::HashSet;
 
+use outlines::base::{OutlineBaseFor, OutlineMaterial};
 use outlines::outline::{Outline, OutlineLabel};
+use outlines::plugin::OutlinePlugin;
 use outlines::rim_effect::RimEffect;
+use outlines::settings::{OutlineDebugMode, OutlineSettings};
 
 /// Adds outlines support and systems for updating outlines behaviour
 pub struct OutlinesPlugin;
@@ -14,6 +18,7 @@ impl Plugin for OutlinesPlugin
     fn build(&self, app: &mut App)
     {
         add_outline::< RimEffect >( app );
+        app.add_systems( Update, cycle_debug_mode );
     }
 }
 
@@ -25,45 +30,37 @@ pub struct OutlineInitSet;
 fn add_outline< O : OutlineLabel >( app : &mut App )
 where 
     Outline< O > : MaterialExtension,
-    MaterialPlugin::< ExtendedMaterial< StandardMaterial, Outline< O > > >: Plugin
+    StandardMaterial : OutlineBaseFor< O >,
+    MaterialPlugin::< OutlineMaterial< O > >: Plugin
 {
-    O::load_shader(app);
-
-    app.add_plugins(
-            MaterialPlugin::< ExtendedMaterial< StandardMaterial, Outline< O > > >::default( )
-        )
-        .register_type::< Outline< O > >()
+    app.add_plugins( OutlinePlugin::< O >::default( ) )
         .add_systems( Update, ( 
             change_time_scale::< O >,
             change_outline_width::< O >,
             set_mode::< O >,
-            update_material_time::< O >  
         ));
 }
 
-/// Updates time for every O outline material 
-fn update_material_time< O : OutlineLabel >(
-    time : Res< Time >,
-    mut materials : ResMut< Assets< ExtendedMaterial< StandardMaterial, Outline< O > > > >,
-)
-where Outline< O > : MaterialExtension{
-    for ( _, material ) in materials.iter_mut( ) {
-        material.extension.add_time( material.extension.time_scale * time.delta_seconds( ) );
-    }
+/// Ids of outline materials used by entities, each once: entities with same base and settings share material,
+/// so it must be edited once per change
+fn unique_ids< O : OutlineLabel >( query : &Query< &Handle< OutlineMaterial< O > > > ) -> HashSet< AssetId< OutlineMaterial< O > > >
+where Outline< O > : MaterialExtension
+{
+    query.iter( ).map( Handle::id ).collect( )
 }
 
 /// For rim_effect outline width is power of Fresnel saturation 
 fn change_outline_width< O : OutlineLabel >(
     keyboard_input : Res< ButtonInput< KeyCode > >, 
     mut mouse_wheel_events: EventReader< MouseWheel >,
-    mut materials : ResMut< Assets< ExtendedMaterial< StandardMaterial, Outline< O > > > >,
-    query: Query<&Handle<ExtendedMaterial< StandardMaterial, Outline< O > >>>
+    mut materials : ResMut< Assets< OutlineMaterial< O > > >,
+    query: Query<&Handle<OutlineMaterial< O >>>
 )
 where Outline< O > : MaterialExtension{
     if keyboard_input.pressed( KeyCode::KeyW ){
         for event in mouse_wheel_events.read( ){
-            for handle in query.iter(){
-                if let Some(material) = materials.get_mut(handle){
+            for id in unique_ids( &query ){
+                if let Some(material) = materials.get_mut(id){
                     if event.y > 0.0 {
                         if material.extension.width < 10000.0{
                             material.extension.width *= 1.25;
@@ -84,14 +81,14 @@ where Outline< O > : MaterialExtension{
 /// Switch width change state: (const width)/(time depended)
 fn set_mode< O : OutlineLabel >(   
     keyboard_input : Res< ButtonInput< KeyCode > >,
-    mut materials : ResMut< Assets< ExtendedMaterial< StandardMaterial, Outline< O > > > >,
-    query: Query<&Handle<ExtendedMaterial< StandardMaterial, Outline< O > >>> 
+    mut materials : ResMut< Assets< OutlineMaterial< O > > >,
+    query: Query<&Handle<OutlineMaterial< O >>> 
 )
 where Outline< O > : MaterialExtension
 {
-    if keyboard_input.pressed( KeyCode::KeyR ){
-        for handle in query.iter(){
-            if let Some(material) = materials.get_mut(handle){
+    if keyboard_input.just_pressed( KeyCode::KeyR ){
+        for id in unique_ids( &query ){
+            if let Some(material) = materials.get_mut(id){
                 let last = material.extension.is_time_related;
                 material.extension.is_time_related = ( !( last > 0 ) ) as u32;
             }
@@ -103,8 +100,8 @@ where Outline< O > : MaterialExtension
 pub fn change_time_scale< O : OutlineLabel >(
     keyboard_input : Res< ButtonInput< KeyCode > >, 
     mut mouse_wheel_events: EventReader< MouseWheel >,
-    mut materials : ResMut< Assets< ExtendedMaterial< StandardMaterial, Outline< O >>>>,
-    query: Query<&Handle<ExtendedMaterial< StandardMaterial, Outline< O > >>> 
+    mut materials : ResMut< Assets< OutlineMaterial< O > > >,
+    query: Query<&Handle<OutlineMaterial< O >>> 
 )
 where Outline< O > : MaterialExtension
 {
@@ -112,8 +109,8 @@ where Outline< O > : MaterialExtension
     {
         for event in mouse_wheel_events.read( )
         {
-            for handle in query.iter(){
-                if let Some(material) = materials.get_mut(handle){
+            for id in unique_ids( &query ){
+                if let Some(material) = materials.get_mut(id){
                     if event.y > 0.0 {
                         material.extension.time_scale *= 1.25;
                     }
@@ -125,4 +122,23 @@ where Outline< O > : MaterialExtension
             }
         }
     }
+}
+
+/// Switch outline debug view: off, Fresnel, N·V, normals, power, coverage
+fn cycle_debug_mode(
+    keyboard_input : Res< ButtonInput< KeyCode > >,
+    mut settings : ResMut< OutlineSettings >,
+)
+{
+    if keyboard_input.just_pressed( KeyCode::KeyD ){
+        settings.debug = match settings.debug {
+            OutlineDebugMode::Off => OutlineDebugMode::Fresnel,
+            OutlineDebugMode::Fresnel => OutlineDebugMode::NormalDotView,
+            OutlineDebugMode::NormalDotView => OutlineDebugMode::Normals,
+            OutlineDebugMode::Normals => OutlineDebugMode::Power,
+            OutlineDebugMode::Power => OutlineDebugMode::Coverage,
+            OutlineDebugMode::Coverage => OutlineDebugMode::Off,
+        };
+        info!( "Debug mode: {:?}", settings.debug );
+    }
 }
\ No newline at end of file
@@ -39,7 +39,7 @@ fn build_diagnostics_ui(mut commands: Commands) {
                 .spawn(NodeBundle {
                     style: Style {
                         width: Val::Px(310.),
-                        height: Val::Px(80.),
+                        height: Val::Px(100.),
                         border: UiRect::all(Val::Px(1.0)),
                         margin: UiRect::all(Val::Px(5.0)),
                         display: Display::Flex,
@@ -98,6 +98,19 @@ fn build_diagnostics_ui(mut commands: Commands) {
                             }),
                             Label,
                         ));
+
+                    parent
+                        .spawn((
+                            TextBundle::from_section(
+                                "Debug view                  : D",
+                                text_help_style.clone(),
+                            )
+                            .with_style(Style {
+                                margin: UiRect::left(Val::Px(10.)),
+                                ..default()
+                            }),
+                            Label,
+                        ));
                 });
         });
 }
\ No newline at end of file
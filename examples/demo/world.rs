@@ -1,8 +1,8 @@
 use std::f32::consts::PI;
 
 use crate::asset_loader::{MeshAssets, TextureAssets};
-use bevy::{pbr::ExtendedMaterial, prelude::*, render::mesh::VertexAttributeValues};
-use outlines::{outline::Outline, rim_effect::RimEffect};
+use bevy::{prelude::*, render::mesh::VertexAttributeValues};
+use outlines::{commands::OutlineCommandsExt, outline::Outline, rim_effect::RimEffect};
 
 /// Setup entities for [`App`]
 pub struct WorldPlugin;
@@ -97,7 +97,7 @@ fn init_shapes_settings(
                 rotation: q.clone(),
                 scale: 3.5 * v.clone(),
             },
-            shape: mesh_assets.pyramida.as_ref().unwrap().clone(),
+            shape: mesh_assets.pyramida.clone(),
         },
         //Character: Capsule + Sphere
         Shape {
@@ -138,35 +138,30 @@ fn map_shapes_transform(
 fn spawn_shapes(
     mut commands: Commands,
     texture_assets: Res< TextureAssets >,
-    mut materials: ResMut<Assets<ExtendedMaterial< StandardMaterial, Outline< RimEffect > >>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
     shapes: Res<Shapes>
 ){
     let t = &texture_assets.ceramic;
+    let material = materials.add(StandardMaterial { 
+        base_color_texture: t.base.clone( ),
+        normal_map_texture : t.normal.clone( ),
+        metallic_roughness_texture : t.metallic.clone( ),
+        occlusion_texture : t.occlusion.clone( ),
+        alpha_mode : AlphaMode::Blend,
+        ..default()
+    });
 
     for shape in shapes.0.iter( )
     {
         let Shape{ transform, shape } = shape;
-        commands.spawn(( 
-            MaterialMeshBundle
-            {
+        commands
+            .spawn(PbrBundle {
                 mesh: shape.clone(),
                 transform: *transform,
-                material : materials.add( 
-                    ExtendedMaterial {
-                        base: StandardMaterial { 
-                            base_color_texture: t.base.clone( ),
-                            normal_map_texture : t.normal.clone( ),
-                            metallic_roughness_texture : t.metallic.clone( ),
-                            occlusion_texture : t.occlusion.clone( ),
-                            alpha_mode : AlphaMode::Blend,
-                            ..default()
-                        },
-                        extension : Outline::< RimEffect >::default( )
-                    } ),    
+                material: material.clone(),
                 ..default( )
-            },
-            RimEffect::default( ),
-        ));
+            })
+            .insert_outline(Outline::< RimEffect >::default( ));
     }
 }
 
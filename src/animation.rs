@@ -0,0 +1,129 @@
+use bevy::animation::AnimationPlayer;
+use bevy::prelude::*;
+
+use super::clock::OutlineClock;
+use super::outline::{Outline, OutlineAsset, OutlineLabel};
+use super::transition::{make_unique, OutlineEase};
+
+/// Outline parameters at `time` of [`OutlineKeyframes`]
+#[ derive( Reflect, Debug, Clone ) ]
+pub struct OutlineKeyframe< O : OutlineLabel >
+{
+    /// Keyframe time in seconds
+    pub time : f32,
+    /// Outline parameters, animation time isn't used
+    pub outline : Outline< O >,
+    /// Easing curve of segment that starts at this keyframe
+    pub ease : OutlineEase,
+}
+
+/// Lightweight keyframe track that animates O outline parameters of entity.
+/// [`AnimationClip`](bevy::animation::AnimationClip) curves can target only transforms and morph weights,
+/// so track time is own or is taken from [`AnimationPlayer`] of `player` entity
+/// to keep outline keys in sync with transform animation of a cutscene.
+#[ derive( Component, Reflect, Debug, Clone ) ]
+pub struct OutlineKeyframes< O : OutlineLabel >
+{
+    keyframes : Vec< OutlineKeyframe< O > >,
+    /// Track restarts after last keyframe
+    pub repeat : bool,
+    /// Entity with [`AnimationPlayer`] whose elapsed time drives track
+    pub player : Option< Entity >,
+    /// Own track time, used when `player` is `None`
+    pub elapsed : f32,
+}
+
+impl< O : OutlineLabel > OutlineKeyframes< O >
+{
+    /// Creates track from `keyframes`, they are sorted by time
+    pub fn new( mut keyframes : Vec< OutlineKeyframe< O > > ) -> Self
+    {
+        keyframes.sort_by( | a, b | a.time.total_cmp( &b.time ) );
+        Self { keyframes, repeat : false, player : None, elapsed : 0.0 }
+    }
+
+    /// Adds keyframe with `outline` at `time` and `ease` curve of next segment
+    pub fn with_keyframe( mut self, time : f32, outline : Outline< O >, ease : OutlineEase ) -> Self
+    {
+        let index = self.keyframes.partition_point( | keyframe | keyframe.time <= time );
+        self.keyframes.insert( index, OutlineKeyframe { time, outline, ease } );
+        self
+    }
+
+    /// Makes track repeat
+    pub fn repeated( mut self ) -> Self
+    {
+        self.repeat = true;
+        self
+    }
+
+    /// Makes track follow [`AnimationPlayer`] of `player`
+    pub fn following( mut self, player : Entity ) -> Self
+    {
+        self.player = Some( player );
+        self
+    }
+
+    /// Returns keyframes sorted by time
+    pub fn keyframes( &self ) -> &[ OutlineKeyframe< O > ]
+    {
+        &self.keyframes
+    }
+
+    /// Returns track duration
+    pub fn duration( &self ) -> f32
+    {
+        self.keyframes.last( ).map_or( 0.0, | keyframe | keyframe.time )
+    }
+
+    /// Returns interpolated outline parameters at `time`
+    pub fn sample( &self, time : f32 ) -> Option< Outline< O > >
+    {
+        let first = self.keyframes.first( )?;
+        let duration = self.duration( );
+        let time = if self.repeat && duration > 0.0 { time.rem_euclid( duration ) } else { time };
+        if time <= first.time {
+            return Some( first.outline.clone( ) );
+        }
+        let next = self.keyframes.partition_point( | keyframe | keyframe.time <= time );
+        let Some( to ) = self.keyframes.get( next ) else {
+            return self.keyframes.last( ).map( | keyframe | keyframe.outline.clone( ) );
+        };
+        let from = &self.keyframes[ next - 1 ];
+        let t = ( time - from.time ) / ( to.time - from.time );
+        Some( from.outline.lerp( &to.outline, from.ease.sample( t ) ) )
+    }
+}
+
+/// Applies [`OutlineKeyframes`] to outline materials of their entities
+pub(crate) fn update_outline_keyframes< O : OutlineLabel, M : OutlineAsset< O > + Clone >(
+    clock : Res< OutlineClock >,
+    mut materials : ResMut< Assets< M > >,
+    mut query : Query< ( &mut Handle< M >, &mut OutlineKeyframes< O > ) >,
+    players : Query< &AnimationPlayer >,
+)
+{
+    for ( mut handle, mut track ) in query.iter_mut( ) {
+        let track_time = match track.player {
+            Some( player ) => match players.get( player ) {
+                Ok( player ) => player.elapsed( ),
+                Err( _ ) => continue,
+            },
+            None => {
+                track.elapsed += clock.delta_seconds( );
+                let duration = track.duration( );
+                if track.repeat && duration > 0.0 {
+                    track.elapsed = track.elapsed.rem_euclid( duration );
+                }
+                track.elapsed
+            }
+        };
+        let Some( sampled ) = track.sample( track_time ) else {
+            continue;
+        };
+        make_unique( &mut handle, &mut materials );
+        if let Some( material ) = materials.get_mut( &*handle ) {
+            material.outline_mut( ).set_parameters( &sampled );
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use bevy::pbr::{ExtendedMaterial, MaterialExtension};
+use bevy::prelude::*;
+
+use super::outline::{Outline, OutlineAsset, OutlineLabel};
+
+/// Outline material, where `B` is base material decorated with `O` outline
+pub type OutlineMaterial< O, B = StandardMaterial > = ExtendedMaterial< B, Outline< O > >;
+
+/// Material that can be used as base of [`OutlineMaterial`].
+///
+/// Outlines with own fragment shader (like [`RimEffect`](crate::rim_effect::RimEffect)) build [`PbrInput`]
+/// from [`StandardMaterial`] bindings, so they are only valid for [`StandardMaterial`] base (lit or unlit).
+/// Toon, unlit and custom-lit materials should use hook outline
+/// ([`RimEffectHook`](crate::rim_effect::RimEffectHook)) that keeps base fragment shader, which calls
+/// `outlines::rim_effect_functions::apply_rim_effect` after own lighting.
+///
+/// Outlines are drawn in forward pass only: with [`DeferredPrepass`](bevy::core_pipeline::prepass::DeferredPrepass)
+/// opaque base with deferred [`OpaqueRendererMethod`](bevy::pbr::OpaqueRendererMethod) is drawn without rim,
+/// so such base should use `OpaqueRendererMethod::Forward`.
+///
+/// [`PbrInput`]: https://github.com/bevyengine/bevy/blob/main/crates/bevy_pbr/src/render/pbr_types.wgsl
+pub trait OutlineBase : Material + Clone { }
+
+impl OutlineBase for StandardMaterial { }
+
+/// Base material that `O` outline shader works with, required by plugin and commands.
+///
+/// [`RimEffect`](crate::rim_effect::RimEffect) is implemented for [`StandardMaterial`] only,
+/// [`RimEffectHook`](crate::rim_effect::RimEffectHook) for every [`OutlineBase`].
+/// Custom outline implements it for bases its shader supports.
+pub trait OutlineBaseFor< O : OutlineLabel > : OutlineBase { }
+
+impl< O : OutlineLabel, B : OutlineBase > OutlineAsset< O > for OutlineMaterial< O, B >
+where Outline< O > : MaterialExtension
+{
+    fn outline( &self ) -> &Outline< O >
+    {
+        &self.extension
+    }
+
+    fn outline_mut( &mut self ) -> &mut Outline< O >
+    {
+        &mut self.extension
+    }
+}
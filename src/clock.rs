@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+/// Time source of outline animation
+#[ derive( Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash, Default ) ]
+pub enum OutlineTimeSource
+{
+    /// [`Time<Virtual>`], affected by game pause and relative speed
+    #[ default ]
+    Virtual,
+    /// [`Time<Real>`], runs while game is paused
+    Real,
+    /// Advanced only by [`OutlineClock::advance_by`]
+    Custom,
+}
+
+/// Clock that drives all outline animation (material time, sync groups, keyframes and transitions)
+#[ derive( Resource, Reflect, Debug, Clone ) ]
+pub struct OutlineClock
+{
+    /// Time source
+    pub source : OutlineTimeSource,
+    /// Speed multiplier of all outline animation
+    pub speed : f32,
+    /// Outlines keep animating with real time while [`Time<Virtual>`] is paused (for example in pause menu)
+    pub run_while_paused : bool,
+    paused : bool,
+    delta : f32,
+    pending : f32,
+}
+
+impl OutlineClock
+{
+    /// Clock with `source` time
+    pub fn new( source : OutlineTimeSource ) -> Self
+    {
+        Self { source, ..default( ) }
+    }
+
+    /// Sets speed multiplier
+    pub fn with_speed( mut self, speed : f32 ) -> Self
+    {
+        self.speed = speed;
+        self
+    }
+
+    /// Makes outlines animate while game is paused
+    pub fn running_while_paused( mut self ) -> Self
+    {
+        self.run_while_paused = true;
+        self
+    }
+
+    /// Stops outline animation
+    pub fn pause( &mut self )
+    {
+        self.paused = true;
+    }
+
+    /// Continues outline animation
+    pub fn resume( &mut self )
+    {
+        self.paused = false;
+    }
+
+    /// Returns `true` if outline animation is stopped
+    pub fn is_paused( &self ) -> bool
+    {
+        self.paused
+    }
+
+    /// Returns seconds passed since last update
+    pub fn delta_seconds( &self ) -> f32
+    {
+        self.delta
+    }
+
+    /// Adds `delta_seconds` to next update of [`OutlineTimeSource::Custom`] clock
+    pub fn advance_by( &mut self, delta_seconds : f32 )
+    {
+        self.pending += delta_seconds;
+    }
+}
+
+impl Default for OutlineClock
+{
+    fn default( ) -> Self
+    {
+        Self
+        {
+            source : OutlineTimeSource::Virtual,
+            speed : 1.0,
+            run_while_paused : false,
+            paused : false,
+            delta : 0.0,
+            pending : 0.0,
+        }
+    }
+}
+
+/// Takes delta of outline clock from its time source, applies pause and speed
+pub(crate) fn update_outline_clock(
+    mut clock : ResMut< OutlineClock >,
+    real : Res< Time< Real > >,
+    virtual_time : Res< Time< Virtual > >,
+)
+{
+    let delta = match clock.source {
+        OutlineTimeSource::Virtual if clock.run_while_paused && virtual_time.is_paused( ) => real.delta_seconds( ),
+        OutlineTimeSource::Virtual => virtual_time.delta_seconds( ),
+        OutlineTimeSource::Real => real.delta_seconds( ),
+        OutlineTimeSource::Custom => std::mem::take( &mut clock.pending ),
+    };
+    clock.delta = if clock.paused { 0.0 } else { delta * clock.speed };
+}
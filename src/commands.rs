@@ -0,0 +1,332 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::pbr::{ExtendedMaterial, MaterialExtension};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use super::base::{OutlineBaseFor, OutlineMaterial};
+use super::outline::{Outline, OutlineLabel};
+use super::pending::OutlinePending;
+use super::propagation::{propagation_targets, OutlineExclude, OutlinePropagation};
+
+/// Outline settings that are compared when searching cached material (time state is skipped, silhouette is included)
+type OutlineKey = Vec< u32 >;
+
+/// Cached outline materials by base material and settings
+type CachedOutlines< O, B > = HashMap< ( AssetId< B >, OutlineKey ), Handle< OutlineMaterial< O, B > > >;
+
+/// Outline materials created by [`OutlineCommandsExt`], shared between entities with same base and settings.
+/// Every material keeps its base, so copies made by animations restore the same base when outline is removed.
+#[ derive( Resource ) ]
+pub struct OutlineMaterialCache< O : OutlineLabel, B : OutlineBaseFor< O > = StandardMaterial >
+where Outline< O > : MaterialExtension
+{
+    entries : CachedOutlines< O, B >,
+}
+
+impl< O : OutlineLabel, B : OutlineBaseFor< O > > Default for OutlineMaterialCache< O, B >
+where Outline< O > : MaterialExtension
+{
+    fn default( ) -> Self
+    {
+        Self { entries : HashMap::default( ) }
+    }
+}
+
+impl< O : OutlineLabel, B : OutlineBaseFor< O > > OutlineMaterialCache< O, B >
+where Outline< O > : MaterialExtension
+{
+    /// Returns cached outline material for `base` with `settings` or creates new one.
+    /// Returns `None` if `base` is not loaded yet.
+    pub fn get_or_create(
+        &mut self,
+        base : Handle< B >,
+        mut settings : Outline< O >,
+        base_materials : &Assets< B >,
+        outline_materials : &mut Assets< OutlineMaterial< O, B > >,
+    ) -> Option< Handle< OutlineMaterial< O, B > > >
+    {
+        self.prune( );
+        let key = ( base.id( ), settings_key( &settings ) );
+        if let Some( material ) = self.entries.get( &key ) {
+            // material edited in place through `Assets::get_mut` doesn't match its key anymore
+            let intact = outline_materials.get( material ).is_some_and( | cached | settings_key( &cached.extension ) == key.1 );
+            if intact {
+                return Some( material.clone( ) );
+            }
+            self.entries.remove( &key );
+        }
+
+        let base_material = base_materials.get( &base )?.clone( );
+        settings.source = Some( base.untyped( ) );
+        let material = outline_materials.add( ExtendedMaterial { base : base_material, extension : settings } );
+        self.entries.insert( key, material.clone( ) );
+        Some( material )
+    }
+
+    /// Drops entries that are used only by cache
+    fn prune( &mut self )
+    {
+        self.entries.retain( | _, material | match material {
+            Handle::Strong( handle ) => std::sync::Arc::strong_count( handle ) > 1,
+            Handle::Weak( _ ) => false,
+        });
+    }
+}
+
+/// Settings part of outline used as cache key
+fn settings_key< O : OutlineLabel >( outline : &Outline< O > ) -> OutlineKey
+{
+    let mut key = vec![ outline.time_scale.to_bits( ), outline.width.to_bits( ), outline.is_time_related, outline.intensity.to_bits( ) ];
+    key.extend( outline.color.as_linear_rgba_f32( ).map( f32::to_bits ) );
+    let falloff = &outline.falloff;
+    key.extend( [ falloff.fade_start, falloff.fade_end, falloff.far_width_scale, falloff.cutoff ].map( f32::to_bits ) );
+    key.extend( outline.silhouette.key( ) );
+    for layer in outline.layers.iter( ) {
+        key.extend( [ layer.time_scale.to_bits( ), layer.width.to_bits( ), layer.is_time_related, layer.blend ] );
+        key.extend( layer.color.as_linear_rgba_f32( ).map( f32::to_bits ) );
+    }
+    key
+}
+
+/// One-line outline management for entities with outline base material.
+/// Methods without `_on` suffix work with [`StandardMaterial`] base.
+pub trait OutlineCommandsExt
+{
+    /// Replaces entity [`StandardMaterial`] with O outline material and inserts O label, waits for material loading.
+    /// Entities with same base and settings share one material, so editing it through [`Assets::get_mut`]
+    /// changes all of them; use [`OutlineCommandsExt::set_outline_color`] or transitions for per-entity changes.
+    fn insert_outline< O : OutlineLabel >( &mut self, settings : Outline< O > ) -> &mut Self
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >;
+
+    /// Restores entity [`StandardMaterial`] and removes O label.
+    /// Entity under outline propagation gets [`OutlineExclude`], so its sub-tree isn't outlined again.
+    fn remove_outline< O : OutlineLabel >( &mut self ) -> &mut Self
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >;
+
+    /// Changes color of entity O outline without affecting other entities
+    fn set_outline_color< O : OutlineLabel >( &mut self, color : Color ) -> &mut Self
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >;
+
+    /// Replaces entity `B` material with O outline material and inserts O label.
+    /// Entities with same base and settings share one material, see [`OutlineCommandsExt::insert_outline`].
+    /// If material isn't loaded yet, [`OutlinePending`] is inserted and outline is applied after loading.
+    fn insert_outline_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &mut self, settings : Outline< O > ) -> &mut Self
+    where Outline< O > : MaterialExtension;
+
+    /// Restores entity `B` material and removes O label.
+    /// Entity under `B` outline propagation gets [`OutlineExclude`], so its sub-tree isn't outlined again.
+    fn remove_outline_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &mut self ) -> &mut Self
+    where Outline< O > : MaterialExtension;
+
+    /// Changes color of entity O outline over `B` material without affecting other entities
+    fn set_outline_color_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &mut self, color : Color ) -> &mut Self
+    where Outline< O > : MaterialExtension;
+
+    /// Outlines entity and its descendants with [`StandardMaterial`], including ones added later
+    fn propagate_outline< O : OutlineLabel >( &mut self, settings : Outline< O > ) -> &mut Self
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >;
+
+    /// Stops outline propagation and restores [`StandardMaterial`] of entity and its descendants
+    fn remove_outline_recursive< O : OutlineLabel >( &mut self ) -> &mut Self
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >;
+
+    /// Outlines entity and its descendants with `B` material, including ones added later
+    fn propagate_outline_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &mut self, settings : Outline< O > ) -> &mut Self
+    where Outline< O > : MaterialExtension;
+
+    /// Stops outline propagation and restores `B` material of entity and its descendants
+    fn remove_outline_recursive_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &mut self ) -> &mut Self
+    where Outline< O > : MaterialExtension;
+}
+
+impl OutlineCommandsExt for EntityCommands< '_ >
+{
+    fn insert_outline< O : OutlineLabel >( &mut self, settings : Outline< O > ) -> &mut Self
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >
+    {
+        self.insert_outline_on::< O, StandardMaterial >( settings )
+    }
+
+    fn remove_outline< O : OutlineLabel >( &mut self ) -> &mut Self
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >
+    {
+        self.remove_outline_on::< O, StandardMaterial >( )
+    }
+
+    fn set_outline_color< O : OutlineLabel >( &mut self, color : Color ) -> &mut Self
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >
+    {
+        self.set_outline_color_on::< O, StandardMaterial >( color )
+    }
+
+    fn insert_outline_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &mut self, settings : Outline< O > ) -> &mut Self
+    where Outline< O > : MaterialExtension
+    {
+        self.add( move | entity : Entity, world : &mut World | insert_outline::< O, B >( entity, world, settings ) )
+    }
+
+    fn remove_outline_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &mut self ) -> &mut Self
+    where Outline< O > : MaterialExtension
+    {
+        self.add( remove_outline::< O, B > )
+    }
+
+    fn set_outline_color_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &mut self, color : Color ) -> &mut Self
+    where Outline< O > : MaterialExtension
+    {
+        self.add( move | entity : Entity, world : &mut World | set_outline_color::< O, B >( entity, world, color ) )
+    }
+
+    fn propagate_outline< O : OutlineLabel >( &mut self, settings : Outline< O > ) -> &mut Self
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >
+    {
+        self.propagate_outline_on::< O, StandardMaterial >( settings )
+    }
+
+    fn remove_outline_recursive< O : OutlineLabel >( &mut self ) -> &mut Self
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >
+    {
+        self.remove_outline_recursive_on::< O, StandardMaterial >( )
+    }
+
+    fn propagate_outline_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &mut self, settings : Outline< O > ) -> &mut Self
+    where Outline< O > : MaterialExtension
+    {
+        self.insert( OutlinePropagation::< O, B >::new( settings ) )
+    }
+
+    fn remove_outline_recursive_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &mut self ) -> &mut Self
+    where Outline< O > : MaterialExtension
+    {
+        self.add( remove_outline_recursive::< O, B > )
+    }
+}
+
+/// Swaps material of `entity` to outline material with `settings`
+fn insert_outline< O : OutlineLabel, B : OutlineBaseFor< O > >( entity : Entity, world : &mut World, settings : Outline< O > )
+where Outline< O > : MaterialExtension
+{
+    let Some( entity_mut ) = world.get_entity( entity ) else {
+        return;
+    };
+    let base = entity_mut.get::< Handle< B > >( ).cloned( );
+    let outlined = entity_mut.get::< Handle< OutlineMaterial< O, B > > >( ).cloned( );
+
+    world.init_resource::< OutlineMaterialCache< O, B > >( );
+    world.resource_scope( | world, mut cache : Mut< OutlineMaterialCache< O, B > > | {
+        let Some( base ) = base.or_else( || outlined.and_then( | material | base_handle( world, &material ) ) ) else {
+            warn!( "Entity {:?} has no base material to outline", entity );
+            return;
+        };
+        let Some( material ) = create_material( world, &mut cache, base, settings.clone( ) ) else {
+            world.entity_mut( entity ).insert( OutlinePending::< O, B >::new( settings ) );
+            return;
+        };
+        world.entity_mut( entity )
+            .remove::< ( Handle< B >, OutlinePending< O, B > ) >( )
+            .insert( ( material, O::default( ) ) );
+    });
+}
+
+/// Swaps outline material of `entity` back to its base material
+fn remove_outline< O : OutlineLabel, B : OutlineBaseFor< O > >( entity : Entity, world : &mut World )
+where Outline< O > : MaterialExtension
+{
+    let Some( mut entity_mut ) = world.get_entity_mut( entity ) else {
+        return;
+    };
+    entity_mut.remove::< ( O, OutlinePending< O, B > ) >( );
+    let Some( material ) = entity_mut.take::< Handle< OutlineMaterial< O, B > > >( ) else {
+        return;
+    };
+    // Restored base material would trigger propagation again
+    let propagated = std::iter::successors( Some( entity ), | entity | world.get::< Parent >( *entity ).map( Parent::get ) )
+        .any( | entity | world.get::< OutlinePropagation< O, B > >( entity ).is_some( ) );
+    if propagated {
+        world.entity_mut( entity ).insert( OutlineExclude );
+    }
+
+    if let Some( base ) = base_handle( world, &material ) {
+        world.entity_mut( entity ).insert( base );
+    }
+}
+
+/// Removes outline propagation of `entity` and outlines of it and its descendants
+fn remove_outline_recursive< O : OutlineLabel, B : OutlineBaseFor< O > >( entity : Entity, world : &mut World )
+where Outline< O > : MaterialExtension
+{
+    let Some( mut entity_mut ) = world.get_entity_mut( entity ) else {
+        return;
+    };
+    entity_mut.remove::< OutlinePropagation< O, B > >( );
+    let targets = propagation_targets(
+        entity,
+        | entity | world.get::< Children >( entity ).map( | children | children.to_vec( ) ),
+        | entity | world.get::< OutlineExclude >( entity ).is_some( ),
+    );
+    for target in targets {
+        remove_outline::< O, B >( target, world );
+    }
+}
+
+/// Replaces outline material of `entity` with copy that has another `color`
+fn set_outline_color< O : OutlineLabel, B : OutlineBaseFor< O > >( entity : Entity, world : &mut World, color : Color )
+where Outline< O > : MaterialExtension
+{
+    let Some( material ) = world.get::< Handle< OutlineMaterial< O, B > > >( entity ).cloned( ) else {
+        warn!( "Entity {:?} has no outline to recolor", entity );
+        return;
+    };
+    let Some( mut settings ) = world.resource::< Assets< OutlineMaterial< O, B > > >( )
+        .get( &material )
+        .map( | material | material.extension.clone( ) ) else {
+        return;
+    };
+    settings.color = color;
+
+    world.init_resource::< OutlineMaterialCache< O, B > >( );
+    world.resource_scope( | world, mut cache : Mut< OutlineMaterialCache< O, B > > | {
+        let Some( base ) = base_handle( world, &material ) else {
+            return;
+        };
+        if let Some( material ) = create_material( world, &mut cache, base, settings ) {
+            world.entity_mut( entity ).insert( material );
+        }
+    });
+}
+
+/// Returns base material of outline `material`, see [`source_base`]
+fn base_handle< O : OutlineLabel, B : OutlineBaseFor< O > >(
+    world : &mut World,
+    material : &Handle< OutlineMaterial< O, B > >,
+) -> Option< Handle< B > >
+where Outline< O > : MaterialExtension
+{
+    world.resource_scope( | world, mut base_materials : Mut< Assets< B > > | {
+        let material = world.resource::< Assets< OutlineMaterial< O, B > > >( ).get( material )?;
+        Some( source_base( material, &mut base_materials ) )
+    })
+}
+
+/// Returns base that outline `material` was created from by commands or,
+/// for materials built by hand, registers copy of embedded base
+pub(crate) fn source_base< O : OutlineLabel, B : OutlineBaseFor< O > >( material : &OutlineMaterial< O, B >, base_materials : &mut Assets< B > ) -> Handle< B >
+where Outline< O > : MaterialExtension
+{
+    material.extension.source::< B >( ).unwrap_or_else( || base_materials.add( material.base.clone( ) ) )
+}
+
+/// Fetches outline material for `base` with `settings` from cache
+fn create_material< O : OutlineLabel, B : OutlineBaseFor< O > >(
+    world : &mut World,
+    cache : &mut OutlineMaterialCache< O, B >,
+    base : Handle< B >,
+    settings : Outline< O >,
+) -> Option< Handle< OutlineMaterial< O, B > > >
+where Outline< O > : MaterialExtension
+{
+    world.resource_scope( | world, mut outline_materials : Mut< Assets< OutlineMaterial< O, B > > > | {
+        let base_materials = world.resource::< Assets< B > >( );
+        cache.get_or_create( base, settings, base_materials, &mut outline_materials )
+    })
+}
@@ -0,0 +1,113 @@
+// `ShaderType` derive generates `check` functions that are never called
+#![ allow( dead_code ) ]
+
+use bevy::prelude::*;
+use bevy::render::render_resource::ShaderType;
+use bevy::utils::HashMap;
+
+use super::outline::{OutlineAsset, OutlineLabel};
+
+/// Camera distance parameters of outline: fade, width scaling and cutoff
+#[ derive( ShaderType, Reflect, Debug, Clone, Copy, PartialEq ) ]
+pub struct OutlineFalloff
+{
+    /// Distance where outline starts to fade and to scale width
+    pub fade_start : f32,
+    /// Distance where outline is faded out completely, fade is disabled if it isn't greater than `fade_start`
+    pub fade_end : f32,
+    /// Width multiplier at `fade_end` (greater value gives wider rim)
+    pub far_width_scale : f32,
+    /// Distance beyond which outline is skipped and isn't animated, `0.0` disables cutoff
+    pub cutoff : f32,
+}
+
+impl OutlineFalloff
+{
+    /// Falloff that fades outline between `fade_start` and `fade_end` and skips it beyond `fade_end`
+    pub fn new( fade_start : f32, fade_end : f32 ) -> Self
+    {
+        Self { fade_start, fade_end, far_width_scale : 1.0, cutoff : fade_end }
+    }
+
+    /// Interpolates parameters between `self` and `other` by `t`
+    pub fn lerp( &self, other : &Self, t : f32 ) -> Self
+    {
+        Self
+        {
+            fade_start : self.fade_start + ( other.fade_start - self.fade_start ) * t,
+            fade_end : self.fade_end + ( other.fade_end - self.fade_end ) * t,
+            far_width_scale : self.far_width_scale + ( other.far_width_scale - self.far_width_scale ) * t,
+            cutoff : self.cutoff + ( other.cutoff - self.cutoff ) * t,
+        }
+    }
+
+    /// Returns `true` if outline is skipped at `distance`
+    pub fn is_cut_off( &self, distance : f32 ) -> bool
+    {
+        self.cutoff > 0.0 && distance > self.cutoff
+    }
+
+    /// Returns progress of fade at `distance`: `0.0` before `fade_start`, `1.0` after `fade_end`
+    pub fn fade_progress( &self, distance : f32 ) -> f32
+    {
+        if self.fade_end <= self.fade_start {
+            return 0.0;
+        }
+        ( ( distance - self.fade_start ) / ( self.fade_end - self.fade_start ) ).clamp( 0.0, 1.0 )
+    }
+
+    /// Returns outline intensity multiplier at `distance`
+    pub fn intensity( &self, distance : f32 ) -> f32
+    {
+        if self.is_cut_off( distance ) {
+            return 0.0;
+        }
+        let t = self.fade_progress( distance );
+        1.0 - t * t * ( 3.0 - 2.0 * t )
+    }
+
+    /// Returns width multiplier at `distance`
+    pub fn width_scale( &self, distance : f32 ) -> f32
+    {
+        1.0 + ( self.far_width_scale - 1.0 ) * self.fade_progress( distance )
+    }
+}
+
+impl Default for OutlineFalloff
+{
+    fn default( ) -> Self
+    {
+        Self
+        {
+            fade_start : 0.0,
+            fade_end : 0.0,
+            far_width_scale : 1.0,
+            cutoff : 0.0,
+        }
+    }
+}
+
+/// Returns nearest camera distance of O outline materials that are used by some entity within cutoff of some camera
+pub(crate) fn materials_in_range< O : OutlineLabel, M : OutlineAsset< O > >(
+    materials : &Assets< M >,
+    entities : &Query< ( &Handle< M >, &GlobalTransform ) >,
+    cameras : &Query< &GlobalTransform, With< Camera > >,
+) -> HashMap< AssetId< M >, f32 >
+{
+    let mut in_range = HashMap::< AssetId< M >, f32 >::default( );
+    for ( handle, transform ) in entities.iter( ) {
+        let Some( material ) = materials.get( handle ) else {
+            continue;
+        };
+        let falloff = &material.outline( ).falloff;
+        let nearest = cameras.iter( )
+            .map( | camera | camera.translation( ).distance( transform.translation( ) ) )
+            .filter( | distance | !falloff.is_cut_off( *distance ) )
+            .reduce( f32::min );
+        if let Some( distance ) = nearest {
+            let entry = in_range.entry( handle.id( ) ).or_insert( distance );
+            *entry = entry.min( distance );
+        }
+    }
+    in_range
+}
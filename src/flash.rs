@@ -0,0 +1,127 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use super::clock::OutlineClock;
+use super::outline::{OutlineAsset, OutlineLabel};
+use super::transition::{lerp_color, make_unique, OutlineEase};
+
+/// One-shot flash of O outline, for example hit feedback. Outline color and intensity jump to flash values
+/// and return to previous ones over `duration` seconds by `curve`, then component is removed
+/// and [`OutlineFlashFinished`] is sent. Flash inserted during another one restarts it from the same previous values.
+#[ derive( Component, Reflect, Debug, Clone ) ]
+pub struct OutlineFlash< O : OutlineLabel >
+{
+    /// Flash color
+    pub color : Color,
+    /// Outline intensity at flash peak
+    pub intensity : f32,
+    /// Flash duration in seconds
+    pub duration : f32,
+    /// Curve of return from flash to previous values
+    pub curve : OutlineEase,
+    elapsed : f32,
+    #[ reflect( ignore ) ]
+    _outline : PhantomData< fn( ) -> O >,
+}
+
+impl< O : OutlineLabel > OutlineFlash< O >
+{
+    /// Flash of `color` over `duration` seconds
+    pub fn new( color : Color, duration : f32 ) -> Self
+    {
+        Self
+        {
+            color,
+            intensity : 2.0,
+            duration,
+            curve : OutlineEase::QuadraticOut,
+            elapsed : 0.0,
+            _outline : PhantomData,
+        }
+    }
+
+    /// Sets intensity at flash peak
+    pub fn with_intensity( mut self, intensity : f32 ) -> Self
+    {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Sets curve of return to previous values
+    pub fn with_curve( mut self, curve : OutlineEase ) -> Self
+    {
+        self.curve = curve;
+        self
+    }
+
+    /// Returns flash amount: `1.0` at start, `0.0` at end
+    pub fn amount( &self ) -> f32
+    {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        1.0 - self.curve.sample( self.elapsed / self.duration )
+    }
+}
+
+/// Sent when [`OutlineFlash`] of `entity` is done
+#[ derive( Event, Debug, Clone, Copy, PartialEq, Eq ) ]
+pub struct OutlineFlashFinished
+{
+    pub entity : Entity,
+}
+
+/// Outline values that flash returns to
+#[ derive( Component ) ]
+pub(crate) struct FlashRestore< O : OutlineLabel >
+{
+    color : Color,
+    intensity : f32,
+    _outline : PhantomData< fn( ) -> O >,
+}
+
+/// Entities with running [`OutlineFlash`] and values it returns to
+type Flashes< 'w, 's, O, M > = Query< 'w, 's, ( Entity, &'static mut Handle< M >, &'static mut OutlineFlash< O >, Option< &'static FlashRestore< O > > ) >;
+
+/// Advances [`OutlineFlash`]es of entities with `M` material
+pub(crate) fn update_outline_flashes< O : OutlineLabel, M : OutlineAsset< O > + Clone >(
+    mut commands : Commands,
+    clock : Res< OutlineClock >,
+    mut materials : ResMut< Assets< M > >,
+    mut query : Flashes< O, M >,
+    mut finished : EventWriter< OutlineFlashFinished >,
+)
+{
+    for ( entity, mut handle, mut flash, restore ) in query.iter_mut( ) {
+        let ( color, intensity ) = match restore {
+            Some( restore ) => ( restore.color, restore.intensity ),
+            None => {
+                make_unique( &mut handle, &mut materials );
+                let Some( material ) = materials.get( &*handle ) else {
+                    continue;
+                };
+                let outline = material.outline( );
+                commands.entity( entity ).insert( FlashRestore::< O >
+                {
+                    color : outline.color,
+                    intensity : outline.intensity,
+                    _outline : PhantomData,
+                });
+                ( outline.color, outline.intensity )
+            }
+        };
+        let amount = flash.amount( );
+        if let Some( material ) = materials.get_mut( &*handle ) {
+            let outline = material.outline_mut( );
+            outline.color = lerp_color( color, flash.color, amount );
+            outline.intensity = intensity + ( flash.intensity - intensity ) * amount;
+        }
+        if amount <= 0.0 {
+            commands.entity( entity ).remove::< ( OutlineFlash< O >, FlashRestore< O > ) >( );
+            finished.send( OutlineFlashFinished { entity } );
+        } else {
+            flash.elapsed += clock.delta_seconds( );
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// `ShaderType` derive generates `check` functions that are never called
+#![ allow( dead_code ) ]
+
+use bevy::prelude::*;
+use bevy::render::render_resource::ShaderType;
+
+use super::transition::lerp_color;
+
+/// Max count of additional layers stacked over base outline
+pub const MAX_OUTLINE_LAYERS : usize = 4;
+
+/// Additional outline stacked over base outline, has own parameters and blend mode
+#[ derive( ShaderType, Reflect, Debug, Clone, Copy, PartialEq ) ]
+pub struct OutlineLayer
+{
+    /// Layer color
+    pub color : Color,
+    /// Layer animation speed
+    pub time_scale : f32,
+    /// Layer animation time
+    pub u_time : f32,
+    /// Layer width (power of Fresnel saturation for rim effect)
+    pub width : f32,
+    /// Set width change state: (const width)/(time depended)
+    pub is_time_related : u32,
+    /// How layer is blended with color below: [`OutlineLayer::BLEND_MIX`], [`OutlineLayer::BLEND_ADD`], [`OutlineLayer::BLEND_MAX`]
+    pub blend : u32,
+}
+
+impl OutlineLayer
+{
+    /// Layer color replaces color below by rim level
+    pub const BLEND_MIX : u32 = 0;
+    /// Layer color scaled by rim level is added to color below
+    pub const BLEND_ADD : u32 = 1;
+    /// Brightest of color below and mixed layer color is taken
+    pub const BLEND_MAX : u32 = 2;
+
+    /// Creates layer with `color`, `width` and `blend` mode
+    pub fn new( color : Color, width : f32, blend : u32 ) -> Self
+    {
+        Self { color, width, blend, ..default( ) }
+    }
+
+    /// Interpolates parameters between `self` and `other` by `t`, discrete parameters switch at half way
+    pub fn lerp( &self, other : &Self, t : f32 ) -> Self
+    {
+        let discrete = if t < 0.5 { self } else { other };
+        Self
+        {
+            color : lerp_color( self.color, other.color, t ),
+            time_scale : self.time_scale + ( other.time_scale - self.time_scale ) * t,
+            u_time : self.u_time,
+            width : self.width + ( other.width - self.width ) * t,
+            is_time_related : discrete.is_time_related,
+            blend : discrete.blend,
+        }
+    }
+
+    /// Updates layer time with own animation speed
+    pub fn advance( &mut self, delta_seconds : f32 )
+    {
+        self.u_time += self.time_scale * delta_seconds;
+    }
+}
+
+impl Default for OutlineLayer
+{
+    fn default( ) -> Self
+    {
+        Self
+        {
+            color : Color::rgba_linear( 0.6, 0.6, 0.0, 1.0 ),
+            time_scale : 1.0,
+            u_time : 0.0,
+            width : 2.0,
+            is_time_related : 0,
+            blend : Self::BLEND_MIX,
+        }
+    }
+}
+
+/// Stack of layers blended in order after base outline.
+/// Fixed size keeps one material type for any layer count.
+#[ derive( ShaderType, Reflect, Debug, Clone, Copy, PartialEq, Default ) ]
+pub struct OutlineLayers
+{
+    layers : [ OutlineLayer; MAX_OUTLINE_LAYERS ],
+    count : u32,
+}
+
+impl OutlineLayers
+{
+    /// Adds layer on top of stack, returns `false` if stack is full
+    pub fn push( &mut self, layer : OutlineLayer ) -> bool
+    {
+        self.insert( self.len( ), layer )
+    }
+
+    /// Inserts layer at `index` of blend order, returns `false` if stack is full or `index` is out of range
+    pub fn insert( &mut self, index : usize, layer : OutlineLayer ) -> bool
+    {
+        let len = self.len( );
+        if len == MAX_OUTLINE_LAYERS || index > len {
+            return false;
+        }
+        self.layers.copy_within( index..len, index + 1 );
+        self.layers[ index ] = layer;
+        self.count += 1;
+        true
+    }
+
+    /// Removes layer at `index` of blend order
+    pub fn remove( &mut self, index : usize ) -> Option< OutlineLayer >
+    {
+        let len = self.len( );
+        if index >= len {
+            return None;
+        }
+        let layer = self.layers[ index ];
+        self.layers.copy_within( index + 1..len, index );
+        self.layers[ len - 1 ] = OutlineLayer::default( );
+        self.count -= 1;
+        Some( layer )
+    }
+
+    /// Removes all layers
+    pub fn clear( &mut self )
+    {
+        *self = Self::default( );
+    }
+
+    /// Returns count of layers
+    pub fn len( &self ) -> usize
+    {
+        self.count as usize
+    }
+
+    /// Returns `true` if there are no layers
+    pub fn is_empty( &self ) -> bool
+    {
+        self.count == 0
+    }
+
+    /// Returns layers in blend order
+    pub fn iter( &self ) -> impl Iterator< Item = &OutlineLayer >
+    {
+        self.layers[ ..self.len( ) ].iter( )
+    }
+
+    /// Returns mutable layers in blend order
+    pub fn iter_mut( &mut self ) -> impl Iterator< Item = &mut OutlineLayer >
+    {
+        let len = self.len( );
+        self.layers[ ..len ].iter_mut( )
+    }
+
+    /// Interpolates layers with same index, layers present only in one stack fade from or to zero alpha
+    pub fn lerp( &self, other : &Self, t : f32 ) -> Self
+    {
+        let mut result = Self { count : self.count.max( other.count ), ..*self };
+        for index in 0..result.len( ) {
+            let from = self.layer_or_transparent( index, other );
+            let to = other.layer_or_transparent( index, self );
+            result.layers[ index ] = from.lerp( &to, t );
+        }
+        result
+    }
+
+    /// Returns layer at `index` or transparent copy of `fallback` layer at same index
+    fn layer_or_transparent( &self, index : usize, fallback : &Self ) -> OutlineLayer
+    {
+        if index < self.len( ) {
+            return self.layers[ index ];
+        }
+        let mut layer = fallback.layers[ index ];
+        layer.color.set_a( 0.0 );
+        layer
+    }
+
+    /// Returns layer at `index` of blend order
+    pub fn get_mut( &mut self, index : usize ) -> Option< &mut OutlineLayer >
+    {
+        let len = self.len( );
+        self.layers[ ..len ].get_mut( index )
+    }
+}
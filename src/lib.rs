@@ -1,3 +1,47 @@
 pub mod rim_effect;
 
-pub mod outline;
\ No newline at end of file
+pub mod outline;
+
+pub mod layers;
+
+pub mod falloff;
+
+pub mod outline_2d;
+
+pub mod base;
+
+pub mod commands;
+
+pub mod plugin;
+
+pub mod view;
+
+pub mod xray;
+
+pub mod transition;
+
+pub mod animation;
+
+pub mod sync;
+
+pub mod clock;
+
+pub mod settings;
+
+pub mod state;
+
+pub mod picking;
+
+pub mod flash;
+
+pub mod proximity;
+
+pub mod silhouette;
+
+pub mod propagation;
+
+pub mod pending;
+
+pub mod rules;
+
+pub mod testing;
\ No newline at end of file
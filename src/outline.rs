@@ -1,20 +1,47 @@
 use bevy::prelude::*;
 use bevy::render::render_resource::AsBindGroup;
 
+use super::falloff::OutlineFalloff;
+use super::layers::OutlineLayers;
+use super::settings::{OutlineDebugMode, OutlineKey, OutlineQuality};
+use super::silhouette::OutlineSilhouetteParams;
+use super::transition::lerp_color;
+use super::view::OutlineViews;
+
+/// Shader module `outlines::outline_types` with types and functions shared by all outline shaders
+pub const TYPES_SHADER_HANDLE : Handle< Shader > =
+    Handle::< Shader >::weak_from_u128( 42572727525244273577 );
+
 /// Label used for identification of outlines
 pub trait OutlineLabel : Component + TypePath + FromReflect + Reflect + Clone + Default + Send + Sync { 
     /// Returns shader handle of certain outline  
     fn shader_handle() -> Handle<Shader>;
     /// Must loads shader from file path 
     fn load_shader(app : &mut App);
+    /// Returns period of outline waveform in `u_time` units, time wraps at it to keep float precision
+    fn time_period( ) -> f32
+    {
+        std::f32::consts::TAU
+    }
+}
+
+/// Asset that carries outline parameters, used by systems shared between 2D and 3D outlines
+pub trait OutlineAsset< O : OutlineLabel > : Asset
+{
+    /// Returns outline parameters
+    fn outline( &self ) -> &Outline< O >;
+    /// Returns mutable outline parameters
+    fn outline_mut( &mut self ) -> &mut Outline< O >;
 }
 
 #[derive( Asset, AsBindGroup, Reflect, Debug, Clone ) ]
+#[ bind_group_data( OutlineKey ) ]
 pub struct Outline< O : OutlineLabel > 
 {
     /// Outline animation speed
     pub time_scale: f32,
-    /// Value that helps change animation and synchonize it with other entities outline material
+    /// Value that helps change animation and synchonize it with other entities outline material,
+    /// see [`OutlineSync`](crate::sync::OutlineSync)
     #[ uniform( 100 ) ]
     pub u_time : f32,
     /// Outline width (power of Fresnel saturation for rim effect)
@@ -23,15 +50,122 @@ pub struct Outline< O : OutlineLabel >
     /// Set width change state: (const width)/(time depended)
     #[ uniform( 102 ) ]
     pub is_time_related : u32,
+    /// Outline color mixed over the lit surface
+    #[ uniform( 103 ) ]
+    pub color : Color,
+    /// Additional outlines blended over this one in stack order
+    #[ uniform( 104 ) ]
+    pub layers : OutlineLayers,
+    /// Per-camera overrides, copied from cameras with [`OutlineViewSettings`](crate::view::OutlineViewSettings)
+    #[ uniform( 105 ) ]
+    pub( crate ) views : OutlineViews,
+    /// Camera distance fade, width scaling and cutoff
+    #[ uniform( 106 ) ]
+    pub falloff : OutlineFalloff,
+    /// Rim level multiplier, used for fading outline in and out
+    #[ uniform( 107 ) ]
+    pub intensity : f32,
+    /// Quality copied from [`OutlineSettings`](crate::settings::OutlineSettings), selects shader defs
+    pub( crate ) quality : OutlineQuality,
+    /// Debug mode copied from [`OutlineSettings`](crate::settings::OutlineSettings), selects shader defs
+    pub( crate ) debug : OutlineDebugMode,
+    /// Group silhouette parameters copied from [`OutlineSilhouette`](crate::silhouette::OutlineSilhouette) of entity or its ancestor
+    #[ uniform( 108 ) ]
+    pub( crate ) silhouette : OutlineSilhouetteParams,
+    /// Base material this outline was created from by [`OutlineCommandsExt`](crate::commands::OutlineCommandsExt),
+    /// kept by copies that animations make, so removed outline restores the original base
+    #[ reflect( ignore ) ]
+    pub( crate ) source : Option< UntypedHandle >,
     _outline : O
 }
 
 impl< O : OutlineLabel > Outline< O >
 {
-    /// Updates time in outline material shader
+    /// Updates time in outline material shader, time wraps at [`OutlineLabel::time_period`]
     pub fn add_time( &mut self, delta_time : f32 )
     {
-        self.u_time += delta_time;
+        self.u_time = ( self.u_time + delta_time ).rem_euclid( O::time_period( ) );
+    }
+
+    /// Sets time of outline and its layers to `phase` seconds scaled by their animation speed.
+    /// Phase is taken in double precision, so it may grow for hours.
+    pub fn set_phase( &mut self, phase : f64 )
+    {
+        let period = O::time_period( ) as f64;
+        self.u_time = ( self.time_scale as f64 * phase ).rem_euclid( period ) as f32;
+        for layer in self.layers.iter_mut( ) {
+            layer.u_time = ( layer.time_scale as f64 * phase ).rem_euclid( period ) as f32;
+        }
+    }
+
+    /// Restarts animation of outline and its layers
+    pub fn reset_time( &mut self )
+    {
+        self.set_phase( 0.0 );
+    }
+
+    /// Copies parameters of `other`, keeps animation time, per-camera state, quality, debug mode, silhouette and source base
+    pub fn set_parameters( &mut self, other : &Self )
+    {
+        let u_time = self.u_time;
+        let views = self.views;
+        let quality = self.quality;
+        let debug = self.debug;
+        let silhouette = self.silhouette;
+        let source = self.source.take( );
+        let mut layers = other.layers;
+        for ( layer, current ) in layers.iter_mut( ).zip( self.layers.iter( ) ) {
+            layer.u_time = current.u_time;
+        }
+        *self = other.clone( );
+        self.u_time = u_time;
+        self.views = views;
+        self.quality = quality;
+        self.debug = debug;
+        self.silhouette = silhouette;
+        self.source = source;
+        self.layers = layers;
+    }
+
+    /// Returns `B` base material this outline was created from by [`OutlineCommandsExt`](crate::commands::OutlineCommandsExt)
+    pub( crate ) fn source< B : Asset >( &self ) -> Option< Handle< B > >
+    {
+        self.source.clone( )?.try_typed::< B >( ).ok( )
+    }
+
+    /// Interpolates parameters between `self` and `other` by `t`.
+    /// Discrete parameters (animation mode, layer count and blend) switch at half way,
+    /// animation time, per-camera state, quality, debug mode, silhouette and source base are taken from `self`.
+    pub fn lerp( &self, other : &Self, t : f32 ) -> Self
+    {
+        let discrete = if t < 0.5 { self } else { other };
+        Self
+        {
+            time_scale : self.time_scale + ( other.time_scale - self.time_scale ) * t,
+            u_time : self.u_time,
+            width : self.width + ( other.width - self.width ) * t,
+            is_time_related : discrete.is_time_related,
+            color : lerp_color( self.color, other.color, t ),
+            layers : self.layers.lerp( &other.layers, t ),
+            views : self.views,
+            quality : self.quality,
+            debug : self.debug,
+            silhouette : self.silhouette,
+            source : self.source.clone( ),
+            falloff : self.falloff.lerp( &other.falloff, t ),
+            intensity : self.intensity + ( other.intensity - self.intensity ) * t,
+            _outline : O::default( ),
+        }
+    }
+
+    /// Updates time of outline and its layers with their animation speed
+    pub fn advance( &mut self, delta_seconds : f32 )
+    {
+        self.add_time( self.time_scale * delta_seconds );
+        for layer in self.layers.iter_mut( ) {
+            layer.advance( delta_seconds );
+            layer.u_time = layer.u_time.rem_euclid( O::time_period( ) );
+        }
     }
 }
 
@@ -45,8 +179,25 @@ impl< O : OutlineLabel > Default for Outline< O >
             u_time : 0.0,
             width : 2.0,
             is_time_related : 0,
+            color : Color::rgba_linear( 0.6, 0.6, 0.0, 1.0 ),
+            layers : OutlineLayers::default( ),
+            views : OutlineViews::default( ),
+            falloff : OutlineFalloff::default( ),
+            intensity : 1.0,
+            quality : OutlineQuality::default( ),
+            debug : OutlineDebugMode::default( ),
+            silhouette : OutlineSilhouetteParams::default( ),
+            source : None,
             _outline : O::default( )
         }
     }
 }
 
+impl< O : OutlineLabel > From< &Outline< O > > for OutlineKey
+{
+    fn from( outline : &Outline< O > ) -> Self
+    {
+        Self { quality : outline.quality, debug : outline.debug, silhouette : outline.silhouette.is_enabled( ) }
+    }
+}
+
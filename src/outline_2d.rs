@@ -0,0 +1,155 @@
+// `ShaderType` derive generates `check` functions that are never called
+#![ allow( dead_code ) ]
+
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_resource::{AsBindGroup, AsBindGroupShaderType, ShaderType};
+use bevy::sprite::{Material2d, Material2dPlugin};
+
+use super::falloff::OutlineFalloff;
+use super::layers::OutlineLayers;
+use super::outline::{Outline, OutlineAsset, OutlineLabel};
+use super::plugin::add_outline_systems;
+use super::settings::{OutlineDebugMode, OutlineKey};
+use super::view::OutlineViews;
+
+/// 2D counterpart of outline material for [`Mesh2d`](bevy::sprite::Mesh2dHandle) and sprite quads.
+/// Edge is found from texture alpha gradient or, without texture, from distance to UV rect border.
+/// Outline width is the same power as in 3D (greater value gives thinner edge), see [`edge_texels`].
+#[ derive( Asset, AsBindGroup, Reflect, Debug, Clone ) ]
+#[ uniform( 0, OutlineMaterial2dUniform ) ]
+#[ bind_group_data( OutlineKey ) ]
+pub struct OutlineMaterial2d< O : OutlineLabel >
+{
+    /// Color multiplied with texture
+    pub base_color : Color,
+    /// Sprite texture, its alpha is used for edge detection
+    #[ texture( 1 ) ]
+    #[ sampler( 2 ) ]
+    pub texture : Option< Handle< Image > >,
+    /// Outline parameters shared with 3D outline
+    pub outline : Outline< O >,
+}
+
+impl< O : OutlineLabel > OutlineMaterial2d< O >
+{
+    /// Creates material for `texture` with `outline` preset
+    pub fn new( texture : Handle< Image >, outline : Outline< O > ) -> Self
+    {
+        Self { base_color : Color::WHITE, texture : Some( texture ), outline }
+    }
+}
+
+impl< O : OutlineLabel > Default for OutlineMaterial2d< O >
+{
+    fn default( ) -> Self
+    {
+        Self { base_color : Color::WHITE, texture : None, outline : Outline::default( ) }
+    }
+}
+
+impl< O : OutlineLabel > OutlineAsset< O > for OutlineMaterial2d< O >
+{
+    fn outline( &self ) -> &Outline< O >
+    {
+        &self.outline
+    }
+
+    fn outline_mut( &mut self ) -> &mut Outline< O >
+    {
+        &mut self.outline
+    }
+}
+
+impl< O : OutlineLabel > From< &OutlineMaterial2d< O > > for OutlineKey
+{
+    fn from( material : &OutlineMaterial2d< O > ) -> Self
+    {
+        // 2D shader has no debug views
+        Self { debug : OutlineDebugMode::Off, ..Self::from( &material.outline ) }
+    }
+}
+
+/// [`OutlineMaterial2d`] has texture
+const TEXTURE_FLAG : u32 = 1;
+
+/// Edge size in texels of textured quad at power `1.0`
+pub const EDGE_TEXELS : f32 = 4.0;
+
+/// Least power used for edge size, so edge stays bounded for zero and negative widths
+const MIN_EDGE_POWER : f32 = 0.1;
+
+/// Returns edge size in texels of textured quad for outline `power` (animated and scaled width),
+/// mirrors `edge_texels` of 2D shader. Size is inverse to power as Fresnel rim is,
+/// so default width `2.0` gives 2 texels edge and greater width gives thinner edge
+pub fn edge_texels( power : f32 ) -> f32
+{
+    EDGE_TEXELS / power.max( MIN_EDGE_POWER )
+}
+
+/// GPU representation of [`OutlineMaterial2d`]
+#[ derive( Clone, Default, ShaderType ) ]
+pub struct OutlineMaterial2dUniform
+{
+    pub base_color : Vec4,
+    pub color : Vec4,
+    pub u_time : f32,
+    pub width : f32,
+    pub is_time_related : u32,
+    pub flags : u32,
+    pub intensity : f32,
+    pub layers : OutlineLayers,
+    pub views : OutlineViews,
+    pub falloff : OutlineFalloff,
+}
+
+impl< O : OutlineLabel > AsBindGroupShaderType< OutlineMaterial2dUniform > for OutlineMaterial2d< O >
+{
+    fn as_bind_group_shader_type( &self, _images : &RenderAssets< Image > ) -> OutlineMaterial2dUniform
+    {
+        OutlineMaterial2dUniform
+        {
+            base_color : self.base_color.as_linear_rgba_f32( ).into( ),
+            color : self.outline.color.as_linear_rgba_f32( ).into( ),
+            u_time : self.outline.u_time,
+            width : self.outline.width,
+            is_time_related : self.outline.is_time_related,
+            flags : if self.texture.is_some( ) { TEXTURE_FLAG } else { 0 },
+            intensity : self.outline.intensity,
+            layers : self.outline.layers,
+            views : self.outline.views,
+            falloff : self.outline.falloff,
+        }
+    }
+}
+
+/// Adds `O` 2D outline support: shader, material plugin, types and animation systems shared with 3D
+pub struct OutlinePlugin2d< O : OutlineLabel >
+{
+    _marker : PhantomData< fn( ) -> O >,
+}
+
+impl< O : OutlineLabel > Default for OutlinePlugin2d< O >
+{
+    fn default( ) -> Self
+    {
+        Self { _marker : PhantomData }
+    }
+}
+
+impl< O : OutlineLabel > Plugin for OutlinePlugin2d< O >
+where
+    OutlineMaterial2d< O > : Material2d,
+    Material2dPlugin::< OutlineMaterial2d< O > > : Plugin
+{
+    fn build( &self, app : &mut App )
+    {
+        O::load_shader( app );
+
+        app.add_plugins( Material2dPlugin::< OutlineMaterial2d< O > >::default( ) )
+            .register_type::< OutlineMaterial2d< O > >( );
+        add_outline_systems::< O, OutlineMaterial2d< O > >( app );
+    }
+}
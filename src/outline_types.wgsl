@@ -0,0 +1,138 @@
+#define_import_path outlines::outline_types
+
+// Additional outline stacked over base outline
+struct OutlineLayer {
+  color : vec4f,
+  time_scale : f32,
+  u_time : f32,
+  width : f32,
+  is_time_related : u32,
+  blend : u32,
+};
+
+const MAX_OUTLINE_LAYERS : u32 = 4u;
+const BLEND_MIX : u32 = 0u;
+const BLEND_ADD : u32 = 1u;
+const BLEND_MAX : u32 = 2u;
+
+// Stack of layers blended in order after base outline
+struct OutlineLayers {
+  layers : array<OutlineLayer, MAX_OUTLINE_LAYERS>,
+  count : u32,
+};
+
+// Per-camera outline overrides
+struct OutlineView {
+  viewport : vec4f,
+  world_position : vec3f,
+  width_scale : f32,
+  intensity : f32,
+  layer_mask : u32,
+  enabled : u32,
+};
+
+const MAX_OUTLINE_VIEWS : u32 = 4u;
+const BASE_LAYER_MASK : u32 = 1u;
+
+// Settings of every camera with overrides and global settings
+struct OutlineViews {
+  views : array<OutlineView, MAX_OUTLINE_VIEWS>,
+  count : u32,
+  // Global rim level multiplier
+  intensity : f32,
+  // Global switch
+  enabled : u32,
+};
+
+// Camera distance fade, width scaling and cutoff
+struct OutlineFalloff {
+  fade_start : f32,
+  // Fade is disabled if it isn't greater than `fade_start`
+  fade_end : f32,
+  far_width_scale : f32,
+  // `0.0` disables cutoff
+  cutoff : f32,
+};
+
+// Group silhouette parameters, zero `radius` disables grouping
+struct OutlineSilhouette {
+  // View depth gap to neighbor pixel that makes fragment silhouette edge
+  depth_threshold : f32,
+  // Distance to tested neighbor pixels
+  radius : f32,
+};
+
+// Returns `true` if outline is skipped at `distance`
+fn is_cut_off( falloff : OutlineFalloff, distance : f32 ) -> bool
+{
+  return falloff.cutoff > 0.0 && distance > falloff.cutoff;
+}
+
+// Progress of fade at `distance`: 0 before `fade_start`, 1 after `fade_end`
+fn fade_progress( falloff : OutlineFalloff, distance : f32 ) -> f32
+{
+  if falloff.fade_end <= falloff.fade_start {
+    return 0.0;
+  }
+  return saturate( ( distance - falloff.fade_start ) / ( falloff.fade_end - falloff.fade_start ) );
+}
+
+// Outline intensity multiplier at `distance`
+fn falloff_intensity( falloff : OutlineFalloff, distance : f32 ) -> f32
+{
+  if is_cut_off( falloff, distance ) {
+    return 0.0;
+  }
+  let t : f32 = fade_progress( falloff, distance );
+  return 1.0 - t * t * ( 3.0 - 2.0 * t );
+}
+
+// Width multiplier at `distance`
+fn falloff_width_scale( falloff : OutlineFalloff, distance : f32 ) -> f32
+{
+  return 1.0 + ( falloff.far_width_scale - 1.0 ) * fade_progress( falloff, distance );
+}
+
+// Animated width: (const width)/(time depended)
+fn rim_power( rim_width : f32, rim_time : f32, rim_time_related : u32 ) -> f32
+{
+  var power : f32 = rim_width;
+  if 0u < rim_time_related {
+    power = sin( rim_time ) * 0.6 + rim_width;
+  }
+  return power;
+}
+
+// Blends `layer_color` over `below` by `level` with layer blend mode
+fn blend_layer( below : vec4f, layer_color : vec4f, level : f32, blend : u32 ) -> vec4f
+{
+  let mixed : vec4f = mix( below, layer_color, saturate( level ) );
+  if blend == BLEND_ADD {
+    return vec4f( below.rgb + layer_color.rgb * layer_color.a * level, below.a );
+  }
+  if blend == BLEND_MAX {
+    return max( below, mixed );
+  }
+  return mixed;
+}
+
+// Mask of stacked layer at `index`
+fn layer_mask( index : u32 ) -> u32
+{
+  return 1u << ( index + 1u );
+}
+
+// Overrides of camera with `world_position` and `viewport`, defaults if camera has none
+fn find_view( views : OutlineViews, world_position : vec3f, viewport : vec4f ) -> OutlineView
+{
+  // Arrays passed by value can't be indexed dynamically, so copy is made
+  var all_views : array<OutlineView, MAX_OUTLINE_VIEWS> = views.views;
+  for ( var i : u32 = 0u; i < min( views.count, MAX_OUTLINE_VIEWS ); i++ ) {
+    let view : OutlineView = all_views[i];
+    if all( abs( view.viewport - viewport ) < vec4f( 0.5 ) )
+      && all( abs( view.world_position - world_position ) < vec3f( 0.001 ) ) {
+      return view;
+    }
+  }
+  return OutlineView( viewport, world_position, 1.0, 1.0, 0xffffffffu, 1u );
+}
@@ -0,0 +1,62 @@
+use std::marker::PhantomData;
+
+use bevy::pbr::MaterialExtension;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use super::base::{OutlineBase, OutlineBaseFor};
+use super::commands::OutlineCommandsExt;
+use super::outline::{Outline, OutlineLabel};
+
+/// O outline requested for entity whose `B` material isn't loaded yet, for example node of loading glTF scene.
+/// Inserted by [`OutlineCommandsExt::insert_outline_on`], outline is applied and component is removed once material is loaded.
+#[ derive( Component, Debug, Clone ) ]
+pub struct OutlinePending< O : OutlineLabel, B : OutlineBaseFor< O > = StandardMaterial >
+{
+    /// Outline that is applied after loading
+    pub outline : Outline< O >,
+    _base : PhantomData< fn( ) -> B >,
+}
+
+impl< O : OutlineLabel, B : OutlineBaseFor< O > > OutlinePending< O, B >
+{
+    /// Outline `outline` is applied after loading
+    pub fn new( outline : Outline< O > ) -> Self
+    {
+        Self { outline, _base : PhantomData }
+    }
+}
+
+/// Returns id of `B` material that became available by `event`
+fn loaded_id< B : OutlineBase >( event : &AssetEvent< B > ) -> Option< AssetId< B > >
+{
+    match event {
+        AssetEvent::Added { id } | AssetEvent::LoadedWithDependencies { id } => Some( *id ),
+        _ => None,
+    }
+}
+
+/// Entities with pending O outline over `B` material
+type Pending< 'w, 's, O, B > = Query< 'w, 's, ( Entity, Ref< 'static, OutlinePending< O, B > >, &'static Handle< B > ) >;
+
+/// Applies pending O outlines of entities whose `B` material got loaded
+pub(crate) fn apply_pending_outlines< O : OutlineLabel, B : OutlineBaseFor< O > >(
+    mut commands : Commands,
+    mut events : EventReader< AssetEvent< B > >,
+    pending : Pending< O, B >,
+    base_materials : Res< Assets< B > >,
+)
+where Outline< O > : MaterialExtension
+{
+    let loaded : HashSet< AssetId< B > > = events.read( ).filter_map( loaded_id ).collect( );
+    for ( entity, request, base ) in pending.iter( ) {
+        // material could be loaded between request and its check, events of it are already read
+        let ready = loaded.contains( &base.id( ) ) || request.is_added( );
+        if !ready || !base_materials.contains( base ) {
+            continue;
+        }
+        commands.entity( entity )
+            .remove::< OutlinePending< O, B > >( )
+            .insert_outline_on::< O, B >( request.outline.clone( ) );
+    }
+}
@@ -0,0 +1,272 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::render::primitives::Aabb;
+use bevy::window::PrimaryWindow;
+
+use super::outline::OutlineLabel;
+use super::plugin::OutlineSystemSet;
+use super::state::OutlineState;
+
+/// Sent when cursor starts to hover `entity` with O label
+#[ derive( Event, Debug, Clone, Copy, PartialEq, Eq ) ]
+pub struct OutlineHoverEnter< O : OutlineLabel >
+{
+    pub entity : Entity,
+    _outline : PhantomData< fn( ) -> O >,
+}
+
+impl< O : OutlineLabel > OutlineHoverEnter< O >
+{
+    /// Cursor entered `entity`
+    pub fn new( entity : Entity ) -> Self
+    {
+        Self { entity, _outline : PhantomData }
+    }
+}
+
+/// Sent when cursor stops to hover `entity` with O label, also when hovered entity is despawned or loses label
+#[ derive( Event, Debug, Clone, Copy, PartialEq, Eq ) ]
+pub struct OutlineHoverExit< O : OutlineLabel >
+{
+    pub entity : Entity,
+    _outline : PhantomData< fn( ) -> O >,
+}
+
+impl< O : OutlineLabel > OutlineHoverExit< O >
+{
+    /// Cursor left `entity`
+    pub fn new( entity : Entity ) -> Self
+    {
+        Self { entity, _outline : PhantomData }
+    }
+}
+
+/// Entity with O label under cursor
+#[ derive( Resource, Debug, Clone, Copy, PartialEq ) ]
+pub struct OutlineHovered< O : OutlineLabel >
+{
+    /// Hovered entity
+    pub entity : Option< Entity >,
+    /// World position of hit point
+    pub point : Vec3,
+    _outline : PhantomData< fn( ) -> O >,
+}
+
+impl< O : OutlineLabel > Default for OutlineHovered< O >
+{
+    fn default( ) -> Self
+    {
+        Self { entity : None, point : Vec3::ZERO, _outline : PhantomData }
+    }
+}
+
+/// Mouse-over picking of meshes with O label: ray from cursor through active [`Camera3d`] is tested
+/// against mesh bounds and triangles. Sends [`OutlineHoverEnter`] and [`OutlineHoverExit`] and, if `highlight` is set,
+/// switches [`OutlineState`] of entity between `Idle` and `Hovered`.
+pub struct OutlinePickingPlugin< O : OutlineLabel >
+{
+    /// Hover changes [`OutlineState`] of picked entities
+    pub highlight : bool,
+    _outline : PhantomData< fn( ) -> O >,
+}
+
+impl< O : OutlineLabel > Default for OutlinePickingPlugin< O >
+{
+    fn default( ) -> Self
+    {
+        Self { highlight : true, _outline : PhantomData }
+    }
+}
+
+impl< O : OutlineLabel > Plugin for OutlinePickingPlugin< O >
+{
+    fn build( &self, app : &mut App )
+    {
+        app.init_resource::< OutlineHovered< O > >( )
+            .add_event::< OutlineHoverEnter< O > >( )
+            .add_event::< OutlineHoverExit< O > >( )
+            .add_systems( Update, pick_hovered::< O >.in_set( OutlineSystemSet::Prepare ) )
+            .add_systems( PostUpdate, clear_removed_hovered::< O > );
+        if self.highlight {
+            app.add_systems( Update, highlight_hovered::< O >.in_set( OutlineSystemSet::Prepare ).after( pick_hovered::< O > ) );
+        }
+    }
+}
+
+/// Entities that can be picked
+type Pickable< 'w, 's, O > = Query< 'w, 's, ( Entity, &'static Handle< Mesh >, &'static GlobalTransform, Option< &'static Aabb >, &'static ViewVisibility ), With< O > >;
+
+/// Finds nearest entity with O label under cursor
+fn pick_hovered< O : OutlineLabel >(
+    windows : Query< &Window, With< PrimaryWindow > >,
+    cameras : Query< ( &Camera, &GlobalTransform ), With< Camera3d > >,
+    pickable : Pickable< O >,
+    meshes : Res< Assets< Mesh > >,
+    mut hovered : ResMut< OutlineHovered< O > >,
+    mut enter : EventWriter< OutlineHoverEnter< O > >,
+    mut exit : EventWriter< OutlineHoverExit< O > >,
+)
+{
+    let ray = windows.get_single( ).ok( )
+        .and_then( | window | window.cursor_position( ) )
+        .and_then( | cursor | cursor_ray( &cameras, cursor ) );
+
+    let mut nearest : Option< ( Entity, f32 ) > = None;
+    if let Some( ray ) = ray {
+        for ( entity, mesh, transform, aabb, visibility ) in pickable.iter( ) {
+            if !visibility.get( ) {
+                continue;
+            }
+            let Some( mesh ) = meshes.get( mesh ) else {
+                continue;
+            };
+            // Ray in mesh space keeps world distance as parameter, because direction isn't normalized
+            let inverse = transform.affine( ).inverse( );
+            let origin = inverse.transform_point3( ray.origin );
+            let direction = inverse.transform_vector3( *ray.direction );
+            if let Some( aabb ) = aabb {
+                if ray_aabb( origin, direction, aabb ).is_none( ) {
+                    continue;
+                }
+            }
+            let Some( distance ) = ray_mesh( origin, direction, mesh ) else {
+                continue;
+            };
+            if nearest.is_some_and( | ( _, nearest ) | nearest <= distance ) {
+                continue;
+            }
+            nearest = Some( ( entity, distance ) );
+        }
+    }
+
+    let entity = nearest.map( | ( entity, _ ) | entity );
+    if let ( Some( ray ), Some( ( _, distance ) ) ) = ( ray, nearest ) {
+        hovered.point = ray.get_point( distance );
+    }
+    if hovered.entity == entity {
+        return;
+    }
+    if let Some( previous ) = hovered.entity {
+        exit.send( OutlineHoverExit::new( previous ) );
+    }
+    if let Some( entity ) = entity {
+        enter.send( OutlineHoverEnter::new( entity ) );
+    }
+    hovered.entity = entity;
+}
+
+/// Clears hovered entity that was despawned or lost O label after picking, so [`OutlineHovered`] never keeps dead entity
+fn clear_removed_hovered< O : OutlineLabel >(
+    mut removed : RemovedComponents< O >,
+    mut hovered : ResMut< OutlineHovered< O > >,
+    mut exit : EventWriter< OutlineHoverExit< O > >,
+)
+{
+    let Some( entity ) = hovered.entity else {
+        removed.clear( );
+        return;
+    };
+    if removed.read( ).any( | removed | removed == entity ) {
+        hovered.entity = None;
+        exit.send( OutlineHoverExit::new( entity ) );
+    }
+}
+
+/// Switches [`OutlineState`] of entities that cursor enters and leaves
+fn highlight_hovered< O : OutlineLabel >(
+    mut enter : EventReader< OutlineHoverEnter< O > >,
+    mut exit : EventReader< OutlineHoverExit< O > >,
+    mut states : Query< &mut OutlineState, With< O > >,
+)
+{
+    for event in exit.read( ) {
+        if let Ok( mut state ) = states.get_mut( event.entity ) {
+            if *state == OutlineState::Hovered {
+                *state = OutlineState::Idle;
+            }
+        }
+    }
+    for event in enter.read( ) {
+        if let Ok( mut state ) = states.get_mut( event.entity ) {
+            if *state == OutlineState::Idle {
+                *state = OutlineState::Hovered;
+            }
+        }
+    }
+}
+
+/// Returns world ray under `cursor` of topmost active 3D camera whose viewport contains cursor
+fn cursor_ray( cameras : &Query< ( &Camera, &GlobalTransform ), With< Camera3d > >, cursor : Vec2 ) -> Option< Ray3d >
+{
+    let mut cameras : Vec< _ > = cameras.iter( ).filter( | ( camera, _ ) | camera.is_active ).collect( );
+    cameras.sort_by_key( | ( camera, _ ) | -camera.order );
+    cameras.into_iter( ).find_map( | ( camera, transform ) | {
+        let viewport = camera.logical_viewport_rect( )?;
+        if !viewport.contains( cursor ) {
+            return None;
+        }
+        camera.viewport_to_world( transform, cursor - viewport.min )
+    })
+}
+
+/// Returns ray parameter of entry into `aabb`
+fn ray_aabb( origin : Vec3, direction : Vec3, aabb : &Aabb ) -> Option< f32 >
+{
+    let min = Vec3::from( aabb.min( ) );
+    let max = Vec3::from( aabb.max( ) );
+    let inverse = direction.recip( );
+    let t1 = ( min - origin ) * inverse;
+    let t2 = ( max - origin ) * inverse;
+    let near = t1.min( t2 ).max_element( );
+    let far = t1.max( t2 ).min_element( );
+    ( far >= near.max( 0.0 ) ).then_some( near.max( 0.0 ) )
+}
+
+/// Returns ray parameter of nearest hit with triangles of `mesh`
+fn ray_mesh( origin : Vec3, direction : Vec3, mesh : &Mesh ) -> Option< f32 >
+{
+    if mesh.primitive_topology( ) != PrimitiveTopology::TriangleList {
+        return None;
+    }
+    let Some( VertexAttributeValues::Float32x3( positions ) ) = mesh.attribute( Mesh::ATTRIBUTE_POSITION ) else {
+        return None;
+    };
+    let indices : Vec< usize > = match mesh.indices( ) {
+        Some( Indices::U16( indices ) ) => indices.iter( ).map( | index | *index as usize ).collect( ),
+        Some( Indices::U32( indices ) ) => indices.iter( ).map( | index | *index as usize ).collect( ),
+        None => ( 0..positions.len( ) ).collect( ),
+    };
+    indices.chunks_exact( 3 )
+        .filter_map( | triangle | {
+            let [ a, b, c ] = [ triangle[ 0 ], triangle[ 1 ], triangle[ 2 ] ].map( | index | positions.get( index ).map( | p | Vec3::from( *p ) ) );
+            ray_triangle( origin, direction, a?, b?, c? )
+        })
+        .reduce( f32::min )
+}
+
+/// Möller–Trumbore ray and triangle intersection, returns ray parameter of hit
+fn ray_triangle( origin : Vec3, direction : Vec3, a : Vec3, b : Vec3, c : Vec3 ) -> Option< f32 >
+{
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = direction.cross( edge2 );
+    let determinant = edge1.dot( p );
+    if determinant.abs( ) < f32::EPSILON {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let s = origin - a;
+    let u = s.dot( p ) * inverse;
+    if !( 0.0..=1.0 ).contains( &u ) {
+        return None;
+    }
+    let q = s.cross( edge1 );
+    let v = direction.dot( q ) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = edge2.dot( q ) * inverse;
+    ( t > 0.0 ).then_some( t )
+}
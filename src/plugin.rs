@@ -0,0 +1,162 @@
+use std::marker::PhantomData;
+
+use bevy::asset::load_internal_asset;
+use bevy::pbr::MaterialExtension;
+use bevy::prelude::*;
+use bevy::render::camera::CameraUpdateSystem;
+use bevy::transform::TransformSystem;
+
+use super::animation::update_outline_keyframes;
+use super::base::{OutlineBaseFor, OutlineMaterial};
+use super::clock::{update_outline_clock, OutlineClock};
+use super::commands::OutlineMaterialCache;
+use super::falloff::materials_in_range;
+use super::flash::{update_outline_flashes, OutlineFlashFinished};
+use super::outline::{Outline, OutlineAsset, OutlineLabel, TYPES_SHADER_HANDLE};
+use super::pending::apply_pending_outlines;
+use super::propagation::{propagate_outlines, OutlineExclude};
+use super::proximity::{update_outline_proximity, OutlineFocus};
+use super::rules::{apply_outline_rules, OutlineRulesPlugin};
+use super::settings::{outlines_enabled, OutlineSettings};
+use super::silhouette::{apply_silhouettes, OutlineSilhouette};
+use super::state::{update_outline_states, OutlineState};
+use super::sync::{advance_sync_groups, update_synced_outlines, OutlineSync, OutlineSyncGroups};
+use super::transition::{update_outline_transitions, OutlineTransitionFinished};
+use super::view::{apply_view_settings, collect_view_settings, OutlineViewSettings, OutlineViews};
+
+/// Ordering of outline systems in [`Update`] and, for cameras, in [`PostUpdate`]
+#[ derive( SystemSet, Debug, Hash, PartialEq, Eq, Clone ) ]
+pub enum OutlineSystemSet
+{
+    /// Gathers state shared by all outlines (clock, sync groups)
+    Prepare,
+    /// Changes outline parameters (time, sync phase, states, keyframes, proximity, transitions, flashes),
+    /// skipped while [`OutlineSettings::enabled`] is `false`
+    Animate,
+    /// Copies shared state to outline materials (silhouette groups)
+    Apply,
+    /// Collects camera settings and copies them with global settings to outline materials, runs in [`PostUpdate`]
+    /// after transform propagation and camera update, so matched camera position and viewport are of current frame
+    View,
+}
+
+/// State and systems shared by all outlines, added once by [`OutlinePlugin`] and 2D outline plugin
+pub struct OutlineCorePlugin;
+
+impl Plugin for OutlineCorePlugin
+{
+    fn build( &self, app : &mut App )
+    {
+        load_internal_asset!( app, TYPES_SHADER_HANDLE, "outline_types.wgsl", Shader::from_wgsl );
+
+        app.init_resource::< OutlineViews >( )
+            .init_resource::< OutlineSyncGroups >( )
+            .init_resource::< OutlineClock >( )
+            .init_resource::< OutlineSettings >( )
+            .add_event::< OutlineTransitionFinished >( )
+            .add_event::< OutlineFlashFinished >( )
+            .register_type::< OutlineViewSettings >( )
+            .register_type::< OutlineSync >( )
+            .register_type::< OutlineState >( )
+            .register_type::< OutlineFocus >( )
+            .register_type::< OutlineClock >( )
+            .register_type::< OutlineSettings >( )
+            .configure_sets( Update, (
+                OutlineSystemSet::Prepare,
+                OutlineSystemSet::Animate.run_if( outlines_enabled ),
+                OutlineSystemSet::Apply,
+            ).chain( ) )
+            .configure_sets( PostUpdate, OutlineSystemSet::View
+                .after( TransformSystem::TransformPropagate )
+                .after( CameraUpdateSystem ) )
+            .add_systems( Update, ( update_outline_clock, advance_sync_groups ).chain( ).in_set( OutlineSystemSet::Prepare ) )
+            .add_systems( PostUpdate, collect_view_settings.in_set( OutlineSystemSet::View ) );
+    }
+}
+
+/// Adds `O` outline support for `B` base material: shader, material plugin, types and animation systems
+pub struct OutlinePlugin< O : OutlineLabel, B : OutlineBaseFor< O > = StandardMaterial >
+{
+    _marker : PhantomData< fn( ) -> ( O, B ) >,
+}
+
+impl< O : OutlineLabel, B : OutlineBaseFor< O > > Default for OutlinePlugin< O, B >
+{
+    fn default( ) -> Self
+    {
+        Self { _marker : PhantomData }
+    }
+}
+
+impl< O : OutlineLabel, B : OutlineBaseFor< O > > Plugin for OutlinePlugin< O, B >
+where
+    Outline< O > : MaterialExtension,
+    MaterialPlugin::< OutlineMaterial< O, B > > : Plugin
+{
+    fn build( &self, app : &mut App )
+    {
+        O::load_shader( app );
+
+        if !app.is_plugin_added::< OutlineRulesPlugin< O, B > >( ) {
+            app.add_plugins( OutlineRulesPlugin::< O, B >::default( ) );
+        }
+        app.add_plugins( MaterialPlugin::< OutlineMaterial< O, B > >::default( ) )
+            .init_resource::< OutlineMaterialCache< O, B > >( )
+            .register_type::< Outline< O > >( )
+            .register_type::< OutlineSilhouette >( )
+            .register_type::< OutlineExclude >( )
+            .add_systems( Update, (
+                propagate_outlines::< O, B >.in_set( OutlineSystemSet::Prepare ).after( apply_outline_rules::< O, B > ),
+                apply_pending_outlines::< O, B >.in_set( OutlineSystemSet::Prepare ),
+                apply_silhouettes::< O, B >.in_set( OutlineSystemSet::Apply ),
+            ));
+        add_outline_systems::< O, OutlineMaterial< O, B > >( app );
+    }
+}
+
+/// Adds systems that animate `M` materials with O outline
+pub(crate) fn add_outline_systems< O : OutlineLabel, M : OutlineAsset< O > + Clone >( app : &mut App )
+{
+    if !app.is_plugin_added::< OutlineCorePlugin >( ) {
+        app.add_plugins( OutlineCorePlugin );
+    }
+
+    app.add_systems( Update, (
+        (
+            update_material_time::< O, M >,
+            update_synced_outlines::< O, M >,
+            update_outline_states::< O, M >,
+            update_outline_keyframes::< O, M >,
+            update_outline_proximity::< O, M >,
+            update_outline_transitions::< O, M >,
+            update_outline_flashes::< O, M >,
+        ).chain( ).in_set( OutlineSystemSet::Animate ),
+    ))
+    .add_systems( PostUpdate, apply_view_settings::< O, M >.in_set( OutlineSystemSet::View ).after( collect_view_settings ) );
+}
+
+/// Updates time for every O outline material, skips materials whose entities are beyond falloff cutoff.
+/// With [`OutlineSettings::max_animated`] only materials nearest to cameras are updated.
+fn update_material_time< O : OutlineLabel, M : OutlineAsset< O > >(
+    clock : Res< OutlineClock >,
+    settings : Res< OutlineSettings >,
+    mut materials : ResMut< Assets< M > >,
+    entities : Query< ( &Handle< M >, &GlobalTransform ) >,
+    cameras : Query< &GlobalTransform, With< Camera > >,
+)
+{
+    let in_range = materials_in_range::< O, M >( &materials, &entities, &cameras );
+    let mut ids : Vec< ( AssetId< M >, f32 ) > = materials.iter( )
+        .filter( | ( id, material ) | material.outline( ).falloff.cutoff <= 0.0 || in_range.contains_key( id ) )
+        .map( | ( id, _ ) | ( id, in_range.get( &id ).copied( ).unwrap_or( f32::INFINITY ) ) )
+        .collect( );
+    if settings.max_animated > 0 && ids.len( ) > settings.max_animated {
+        ids.sort_by( | ( _, a ), ( _, b ) | a.total_cmp( b ) );
+        ids.truncate( settings.max_animated );
+    }
+    for ( id, _ ) in ids {
+        if let Some( material ) = materials.get_mut( id ) {
+            material.outline_mut( ).advance( clock.delta_seconds( ) );
+        }
+    }
+}
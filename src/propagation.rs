@@ -0,0 +1,94 @@
+use std::marker::PhantomData;
+
+use bevy::pbr::MaterialExtension;
+use bevy::prelude::*;
+
+use super::base::{OutlineBaseFor, OutlineMaterial};
+use super::commands::OutlineCommandsExt;
+use super::outline::{Outline, OutlineLabel};
+use super::pending::OutlinePending;
+
+/// Outlines entity and its descendants with `B` base material by O outline `outline`.
+/// Newly added descendants (for example equipped weapon) pick it up too, sub-trees under [`OutlineExclude`] are skipped.
+/// Inserted by [`OutlineCommandsExt::propagate_outline_on`], only propagation of the same `B` acts on it.
+#[ derive( Component, Reflect, Debug, Clone ) ]
+pub struct OutlinePropagation< O : OutlineLabel, B : OutlineBaseFor< O > = StandardMaterial >
+{
+    /// Outline of every descendant
+    pub outline : Outline< O >,
+    #[ reflect( ignore ) ]
+    _base : PhantomData< fn( ) -> B >,
+}
+
+impl< O : OutlineLabel, B : OutlineBaseFor< O > > OutlinePropagation< O, B >
+{
+    /// Propagation of `outline`
+    pub fn new( outline : Outline< O > ) -> Self
+    {
+        Self { outline, _base : PhantomData }
+    }
+}
+
+/// Stops outline propagation into entity and its sub-tree, for example particle attachments and VFX meshes.
+/// [`OutlineCommandsExt::remove_outline`] inserts it into entity under propagation, so outline isn't propagated again
+#[ derive( Component, Reflect, Debug, Clone, Copy, PartialEq, Eq, Default ) ]
+pub struct OutlineExclude;
+
+/// Returns `root` and its descendants, sub-trees under [`OutlineExclude`] are skipped
+pub(crate) fn propagation_targets(
+    root : Entity,
+    children : impl Fn( Entity ) -> Option< Vec< Entity > >,
+    excluded : impl Fn( Entity ) -> bool,
+) -> Vec< Entity >
+{
+    let mut targets = Vec::new( );
+    let mut stack = vec![ root ];
+    while let Some( entity ) = stack.pop( ) {
+        if excluded( entity ) {
+            continue;
+        }
+        targets.push( entity );
+        stack.extend( children( entity ).unwrap_or_default( ) );
+    }
+    targets
+}
+
+/// Entities whose hierarchy or base material changed since last check
+type Reshaped< 'w, 's, B > = Query< 'w, 's, (), Or< ( Changed< Children >, Added< Handle< B > > ) > >;
+
+/// Outlines descendants of [`OutlinePropagation`] roots that have `B` material and no O outline yet,
+/// not loaded materials are outlined after loading by [`OutlinePending`](crate::pending::OutlinePending).
+/// Hierarchies are walked only when they or propagation settings change, for example when scene is spawned.
+/// Changed propagation settings are applied to already outlined descendants too.
+pub(crate) fn propagate_outlines< O : OutlineLabel, B : OutlineBaseFor< O > >(
+    mut commands : Commands,
+    roots : Query< ( Entity, Ref< OutlinePropagation< O, B > > ) >,
+    children : Query< &Children >,
+    excluded : Query< (), With< OutlineExclude > >,
+    bases : Query< Has< OutlinePending< O, B > >, With< Handle< B > > >,
+    outlined : Query< (), With< Handle< OutlineMaterial< O, B > > > >,
+    reshaped : Reshaped< B >,
+)
+where Outline< O > : MaterialExtension
+{
+    let reshaped = !reshaped.is_empty( );
+    for ( root, propagation ) in roots.iter( ) {
+        if !reshaped && !propagation.is_changed( ) {
+            continue;
+        }
+        let targets = propagation_targets(
+            root,
+            | entity | children.get( entity ).ok( ).map( | children | children.to_vec( ) ),
+            | entity | excluded.contains( entity ),
+        );
+        for entity in targets {
+            let needs_outline = match bases.get( entity ) {
+                Ok( pending ) => !pending || propagation.is_changed( ),
+                Err( _ ) => propagation.is_changed( ) && outlined.contains( entity ),
+            };
+            if needs_outline {
+                commands.entity( entity ).insert_outline_on::< O, B >( propagation.outline.clone( ) );
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use super::outline::{OutlineAsset, OutlineLabel};
+use super::transition::{make_unique, OutlineEase};
+
+/// Entity that [`OutlineProximity`] distances are measured to, for example player
+#[ derive( Component, Reflect, Debug, Clone, Copy, PartialEq, Eq, Default ) ]
+pub struct OutlineFocus;
+
+/// Drives O outline intensity of entity by distance to nearest [`OutlineFocus`]:
+/// full `intensity` within `near`, zero beyond `far` and `curve` in between.
+/// Equal `near` and `far` just turn outline on within range.
+#[ derive( Component, Reflect, Debug, Clone, Copy, PartialEq ) ]
+pub struct OutlineProximity< O : OutlineLabel >
+{
+    /// Distance of full intensity
+    pub near : f32,
+    /// Distance of zero intensity
+    pub far : f32,
+    /// Outline intensity near focus
+    pub intensity : f32,
+    /// Curve of intensity fall from `near` to `far`
+    pub curve : OutlineEase,
+    #[ reflect( ignore ) ]
+    _outline : PhantomData< fn( ) -> O >,
+}
+
+impl< O : OutlineLabel > OutlineProximity< O >
+{
+    /// Outline fades from `near` to `far` distance
+    pub fn new( near : f32, far : f32 ) -> Self
+    {
+        Self
+        {
+            near,
+            far,
+            intensity : 1.0,
+            curve : OutlineEase::SineInOut,
+            _outline : PhantomData,
+        }
+    }
+
+    /// Outline is turned on within `range`
+    pub fn within( range : f32 ) -> Self
+    {
+        Self::new( range, range )
+    }
+
+    /// Sets intensity near focus
+    pub fn with_intensity( mut self, intensity : f32 ) -> Self
+    {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Sets curve of intensity fall
+    pub fn with_curve( mut self, curve : OutlineEase ) -> Self
+    {
+        self.curve = curve;
+        self
+    }
+
+    /// Returns outline intensity at `distance` from focus
+    pub fn intensity_at( &self, distance : f32 ) -> f32
+    {
+        if distance <= self.near {
+            return self.intensity;
+        }
+        if distance >= self.far {
+            return 0.0;
+        }
+        let t = ( distance - self.near ) / ( self.far - self.near );
+        self.intensity * ( 1.0 - self.curve.sample( t ) )
+    }
+}
+
+/// Sets intensity of outline materials of entities with [`OutlineProximity`] from distance to nearest focus.
+/// Outlines are hidden while there is no focus.
+pub(crate) fn update_outline_proximity< O : OutlineLabel, M : OutlineAsset< O > + Clone >(
+    mut materials : ResMut< Assets< M > >,
+    focuses : Query< &GlobalTransform, With< OutlineFocus > >,
+    mut query : Query< ( &mut Handle< M >, &OutlineProximity< O >, &GlobalTransform ) >,
+)
+{
+    for ( mut handle, proximity, transform ) in query.iter_mut( ) {
+        let intensity = focuses.iter( )
+            .map( | focus | focus.translation( ).distance( transform.translation( ) ) )
+            .reduce( f32::min )
+            .map_or( 0.0, | distance | proximity.intensity_at( distance ) );
+        let outdated = materials.get( &*handle ).is_some_and( | material | material.outline( ).intensity != intensity );
+        if !outdated {
+            continue;
+        }
+        make_unique( &mut handle, &mut materials );
+        if let Some( material ) = materials.get_mut( &*handle ) {
+            material.outline_mut( ).intensity = intensity;
+        }
+    }
+}
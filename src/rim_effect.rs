@@ -1,12 +1,27 @@
 use bevy::{asset::load_internal_asset, prelude::*};
-use bevy::pbr::MaterialExtension;
-use bevy::render::render_resource::ShaderRef;
+use bevy::pbr::{MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline};
+use bevy::render::mesh::MeshVertexBufferLayout;
+use bevy::render::render_resource::{RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError};
+use bevy::sprite::{Material2d, Material2dKey};
 
+use super::base::{OutlineBase, OutlineBaseFor};
 use super::outline::{Outline, OutlineLabel};
+use super::outline_2d::OutlineMaterial2d;
 
-pub const SHADER_HANDLE: Handle::<Shader> = 
+pub const SHADER_HANDLE: Handle::<Shader> =
     Handle::<Shader>::weak_from_u128(42572727525244273574);
 
+/// Shader module `outlines::rim_effect_functions` with rim effect uniforms and math
+pub const FUNCTIONS_SHADER_HANDLE: Handle::<Shader> =
+    Handle::<Shader>::weak_from_u128(42572727525244273575);
+
+/// 2D rim effect shader for [`OutlineMaterial2d`]
+pub const SHADER_2D_HANDLE: Handle::<Shader> =
+    Handle::<Shader>::weak_from_u128(42572727525244273576);
+
+/// Shader def that is set for base material fragment shader decorated with [`RimEffectHook`]
+pub const HOOK_SHADER_DEF: &str = "RIM_EFFECT_HOOK";
+
 #[ derive( Reflect, Component, Clone, Hash, Default ) ]
 pub struct RimEffect;
 
@@ -16,14 +31,184 @@ impl OutlineLabel for RimEffect {
     }
 
     fn load_shader(app : &mut App){
+        load_functions_shader(app);
         load_internal_asset!(app, Self::shader_handle(), "rim_effect.wgsl", Shader::from_wgsl);
+        load_internal_asset!(app, SHADER_2D_HANDLE, "rim_effect_2d.wgsl", Shader::from_wgsl);
     }
 }
 
-impl MaterialExtension for Outline< RimEffect > 
+/// `rim_effect.wgsl` builds `PbrInput` from [`StandardMaterial`] bindings
+impl OutlineBaseFor< RimEffect > for StandardMaterial { }
+
+impl MaterialExtension for Outline< RimEffect >
 {
-    fn fragment_shader( ) -> ShaderRef 
+    fn fragment_shader( ) -> ShaderRef
     {
         SHADER_HANDLE.into()
     }
-}
\ No newline at end of file
+
+    fn specialize(
+        _pipeline : &MaterialExtensionPipeline,
+        descriptor : &mut RenderPipelineDescriptor,
+        _layout : &MeshVertexBufferLayout,
+        key : MaterialExtensionKey< Self >,
+    ) -> Result< (), SpecializedMeshPipelineError >
+    {
+        key.bind_group_data.specialize( descriptor );
+        Ok( () )
+    }
+}
+
+impl Material2d for OutlineMaterial2d< RimEffect >
+{
+    fn fragment_shader( ) -> ShaderRef
+    {
+        SHADER_2D_HANDLE.into()
+    }
+
+    fn specialize(
+        descriptor : &mut RenderPipelineDescriptor,
+        _layout : &MeshVertexBufferLayout,
+        key : Material2dKey< Self >,
+    ) -> Result< (), SpecializedMeshPipelineError >
+    {
+        key.bind_group_data.specialize( descriptor );
+        Ok( () )
+    }
+}
+
+/// Rim effect for base materials that don't produce `PbrInput` (toon, unlit, custom-lit).
+/// Base fragment shader is kept and must apply outline itself after lighting:
+///
+/// ```wgsl
+/// #ifdef RIM_EFFECT_HOOK
+/// #import outlines::rim_effect_functions::apply_rim_effect
+/// #endif
+/// ...
+/// #ifdef RIM_EFFECT_HOOK
+///   color = apply_rim_effect( color, normalize( in.world_normal ), V, in.world_position.xyz );
+/// #endif
+/// ```
+#[ derive( Reflect, Component, Clone, Hash, Default ) ]
+pub struct RimEffectHook;
+
+impl OutlineLabel for RimEffectHook {
+    fn shader_handle() -> Handle::<Shader>{
+        FUNCTIONS_SHADER_HANDLE
+    }
+
+    fn load_shader(app : &mut App){
+        load_functions_shader(app);
+    }
+}
+
+impl< B : OutlineBase > OutlineBaseFor< RimEffectHook > for B { }
+
+impl MaterialExtension for Outline< RimEffectHook >
+{
+    fn specialize(
+        _pipeline : &MaterialExtensionPipeline,
+        descriptor : &mut RenderPipelineDescriptor,
+        _layout : &MeshVertexBufferLayout,
+        key : MaterialExtensionKey< Self >,
+    ) -> Result< (), SpecializedMeshPipelineError >
+    {
+        if let Some( fragment ) = descriptor.fragment.as_mut( ) {
+            fragment.shader_defs.push( HOOK_SHADER_DEF.into( ) );
+        }
+        key.bind_group_data.specialize( descriptor );
+        Ok( () )
+    }
+}
+
+/// Loads `outlines::rim_effect_functions` shader module
+fn load_functions_shader(app : &mut App){
+    load_internal_asset!(app, FUNCTIONS_SHADER_HANDLE, "rim_effect_functions.wgsl", Shader::from_wgsl);
+}
+
+/// Animated width power, mirrors `rim_power` of `outlines::outline_types` shader module.
+/// Mirrors are compared with composed shader functions by `tests/shaders.rs`
+pub fn rim_power( width : f32, time : f32, is_time_related : u32 ) -> f32
+{
+    if is_time_related > 0 {
+        return time.sin( ) * 0.6 + width;
+    }
+    width
+}
+
+/// Rim level for unit `normal` and `view` vectors with width `power`,
+/// mirrors `rim_level` of `outlines::rim_effect_functions` shader module
+pub fn rim_level( normal : Vec3, view : Vec3, power : f32 ) -> f32
+{
+    let fresnel = 1.0 - normal.dot( view ).max( 0.0001 );
+    2.0 * fresnel.clamp( 0.0, 1.0 ).powf( power )
+}
+
+/// Rim level of base outline `params` at animation `time` for unit `normal` and `view` (surface to camera) vectors,
+/// mirrors `rim_effect` of `outlines::rim_effect_functions` shader module.
+/// Intensity, falloff and per-camera settings aren't applied, as in shader function.
+/// Useful for gameplay queries, for example whether point of mesh is highlighted from camera.
+pub fn rim_intensity< O : OutlineLabel >( normal : Vec3, view : Vec3, params : &Outline< O >, time : f32 ) -> f32
+{
+    rim_level( normal, view, rim_power( params.width, time, params.is_time_related ) )
+}
+
+#[ cfg( test ) ]
+mod tests
+{
+    use super::*;
+
+    /// Outline with `width` and animation mode
+    fn outline( width : f32, is_time_related : u32 ) -> Outline< RimEffect >
+    {
+        let mut outline = Outline::< RimEffect >::default( );
+        outline.width = width;
+        outline.is_time_related = is_time_related;
+        outline
+    }
+
+    #[ test ]
+    fn facing_camera_has_no_rim( )
+    {
+        let level = rim_intensity( Vec3::Z, Vec3::Z, &outline( 2.0, 0 ), 0.0 );
+        assert!( level.abs( ) < 1e-6, "{level}" );
+    }
+
+    #[ test ]
+    fn grazing_angle_has_full_rim( )
+    {
+        // N·V is clamped to 0.0001, so perpendicular and back-facing normals give 2 * 0.9999^power
+        let expected = 2.0 * 0.9999_f32.powf( 2.0 );
+        for normal in [ Vec3::X, Vec3::NEG_Z ] {
+            let level = rim_intensity( normal, Vec3::Z, &outline( 2.0, 0 ), 0.0 );
+            assert!( ( level - expected ).abs( ) < 1e-6, "{normal}: {level}" );
+        }
+    }
+
+    #[ test ]
+    fn sampled_inputs_match_documented_formula( )
+    {
+        // ( N·V, width, time, is_time_related, expected 2 * ( 1 - N·V ) ^ ( width + 0.6 sin time ) )
+        let samples : [ ( f32, f32, f32, u32, f32 ); 6 ] = [
+            ( 0.5, 2.0, 0.0, 0, 0.5 ),
+            ( 0.5, 1.0, 0.0, 0, 1.0 ),
+            ( 0.75, 3.0, 1.0, 0, 0.03125 ),
+            ( 0.5, 2.0, std::f32::consts::FRAC_PI_2, 1, 2.0 * 0.5_f32.powf( 2.6 ) ),
+            ( 0.5, 2.0, -std::f32::consts::FRAC_PI_2, 1, 2.0 * 0.5_f32.powf( 1.4 ) ),
+            ( 0.2, 0.5, 0.0, 1, 2.0 * 0.8_f32.powf( 0.5 ) ),
+        ];
+        for ( cos, width, time, is_time_related, expected ) in samples {
+            let normal = Vec3::new( ( 1.0 - cos * cos ).sqrt( ), 0.0, cos );
+            let level = rim_intensity( normal, Vec3::Z, &outline( width, is_time_related ), time );
+            assert!( ( level - expected ).abs( ) < 1e-5, "N·V {cos}, width {width}, time {time}: {level} != {expected}" );
+        }
+    }
+
+    #[ test ]
+    fn time_is_ignored_for_constant_width( )
+    {
+        let params = outline( 2.0, 0 );
+        let normal = Vec3::new( 0.6, 0.0, 0.8 );
+        assert_eq!( rim_intensity( normal, Vec3::Z, &params, 0.0 ), rim_intensity( normal, Vec3::Z, &params, 1.3 ) );
+    }
+}
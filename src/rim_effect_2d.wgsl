@@ -0,0 +1,137 @@
+#import bevy_sprite::{
+  mesh2d_vertex_output::VertexOutput,
+  mesh2d_view_bindings::view,
+}
+#import outlines::outline_types::{
+  OutlineLayer,
+  OutlineLayers,
+  OutlineView,
+  OutlineViews,
+  OutlineFalloff,
+  MAX_OUTLINE_LAYERS,
+  BASE_LAYER_MASK,
+  rim_power,
+  blend_layer,
+  layer_mask,
+  find_view,
+  is_cut_off,
+  falloff_intensity,
+  falloff_width_scale
+}
+
+#ifdef TONEMAP_IN_SHADER
+#import bevy_core_pipeline::tonemapping
+#endif
+
+struct OutlineMaterial2d {
+  base_color : vec4f,
+  // Outline color mixed over the sprite
+  color : vec4f,
+  // Value that helps change animation and synchonize it with other entities outline material
+  u_time : f32,
+  // Outline width (power of edge saturation as in 3D, converted to edge size in texels for textured quads)
+  width : f32,
+  // Set width change state: (const width)/(time depended)
+  is_time_related : u32,
+  flags : u32,
+  // Rim level multiplier, used for fading outline in and out
+  intensity : f32,
+  // Additional outlines blended over base outline in stack order
+  layers : OutlineLayers,
+  // Per-camera overrides
+  views : OutlineViews,
+  // Camera distance fade, width scaling and cutoff
+  falloff : OutlineFalloff,
+};
+const TEXTURE_FLAG : u32 = 1u;
+// Edge size in texels of textured quad at power 1
+const EDGE_TEXELS : f32 = 4.0;
+// Least power used for edge size
+const MIN_EDGE_POWER : f32 = 0.1;
+
+@group(2) @binding(0) var<uniform> material : OutlineMaterial2d;
+@group(2) @binding(1) var texture : texture_2d<f32>;
+@group(2) @binding(2) var texture_sampler : sampler;
+
+// Edge size in texels for `power`, inverse to it as Fresnel rim is, so greater width gives thinner edge
+fn edge_texels( power : f32 ) -> f32
+{
+  return EDGE_TEXELS / max( power, MIN_EDGE_POWER );
+}
+
+// Edge level from alpha gradient: difference of max and min alpha around `uv` at `texels` distance
+fn alpha_edge( uv : vec2f, texels : f32 ) -> f32
+{
+  let texel : vec2f = texels / vec2f( textureDimensions( texture ) );
+  var min_alpha : f32 = 1.0;
+  var max_alpha : f32 = 0.0;
+  for ( var i : i32 = 0; i < 8; i++ ) {
+    let angle : f32 = f32( i ) * 0.785398;
+    let offset : vec2f = vec2f( cos( angle ), sin( angle ) ) * texel;
+    let alpha : f32 = textureSampleLevel( texture, texture_sampler, uv + offset, 0.0 ).a;
+    min_alpha = min( min_alpha, alpha );
+    max_alpha = max( max_alpha, alpha );
+  }
+  return saturate( max_alpha - min_alpha );
+}
+
+// Edge level from distance to UV rect border, analogue of Fresnel term
+fn rect_edge( uv : vec2f, power : f32 ) -> f32
+{
+  let border : vec2f = min( uv, 1.0 - uv );
+  let inside : f32 = saturate( 2.0 * min( border.x, border.y ) );
+  return 2.0 * pow( saturate( 1.0 - inside ), power );
+}
+
+// Edge level of sprite for given width, `width_scale` widens edge
+fn edge_level( uv : vec2f, power : f32, width_scale : f32 ) -> f32
+{
+  if ( material.flags & TEXTURE_FLAG ) != 0u {
+    return saturate( alpha_edge( uv, edge_texels( power / width_scale ) ) );
+  }
+  return saturate( rect_edge( uv, power / width_scale ) );
+}
+
+@fragment
+fn fragment(
+  mesh : VertexOutput,
+) -> @location(0) vec4f
+{
+  var output_color : vec4f = material.base_color;
+#ifdef VERTEX_COLORS
+  output_color = output_color * mesh.color;
+#endif
+  if ( material.flags & TEXTURE_FLAG ) != 0u {
+    output_color = output_color * textureSample( texture, texture_sampler, mesh.uv );
+  }
+  let outline_view : OutlineView = find_view( material.views, view.world_position, view.viewport );
+  let distance : f32 = length( mesh.world_position.xyz - view.world_position );
+  let width_scale : f32 = outline_view.width_scale * falloff_width_scale( material.falloff, distance );
+  if material.views.enabled != 0u && outline_view.enabled != 0u && !is_cut_off( material.falloff, distance ) {
+    let level_scale : f32 = material.views.intensity * outline_view.intensity * material.intensity
+      * falloff_intensity( material.falloff, distance );
+    var alpha : f32 = output_color.a;
+    if ( outline_view.layer_mask & BASE_LAYER_MASK ) != 0u {
+      let power : f32 = rim_power( material.width, material.u_time, material.is_time_related );
+      let edge : f32 = saturate( edge_level( mesh.uv, power, width_scale ) * level_scale );
+      alpha = max( alpha, edge * material.color.a );
+      output_color = vec4f( mix( output_color.rgb, material.color.rgb, edge ), alpha );
+    }
+#ifndef OUTLINE_QUALITY_LOW
+    for ( var i : u32 = 0u; i < min( material.layers.count, MAX_OUTLINE_LAYERS ); i++ ) {
+      if ( outline_view.layer_mask & layer_mask( i ) ) == 0u {
+        continue;
+      }
+      let layer : OutlineLayer = material.layers.layers[i];
+      let power : f32 = rim_power( layer.width, layer.u_time, layer.is_time_related );
+      let level : f32 = saturate( edge_level( mesh.uv, power, width_scale ) * level_scale );
+      alpha = max( alpha, level * layer.color.a );
+      output_color = vec4f( blend_layer( output_color, layer.color, level, layer.blend ).rgb, alpha );
+    }
+#endif
+  }
+#ifdef TONEMAP_IN_SHADER
+  output_color = tonemapping::tone_mapping( output_color, view.color_grading );
+#endif
+  return output_color;
+}
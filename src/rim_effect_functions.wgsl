@@ -0,0 +1,157 @@
+#define_import_path outlines::rim_effect_functions
+
+#import bevy_pbr::mesh_view_bindings::view
+#import outlines::outline_types::{
+  OutlineLayer,
+  OutlineLayers,
+  OutlineView,
+  OutlineViews,
+  OutlineFalloff,
+  OutlineSilhouette,
+  MAX_OUTLINE_LAYERS,
+  BASE_LAYER_MASK,
+  rim_power,
+  blend_layer,
+  layer_mask,
+  find_view,
+  is_cut_off,
+  falloff_intensity,
+  falloff_width_scale
+}
+#ifdef OUTLINE_SILHOUETTE
+#ifdef DEPTH_PREPASS
+#import bevy_pbr::prepass_utils::prepass_depth
+#import bevy_pbr::view_transformations::{position_world_to_ndc, ndc_to_uv, depth_ndc_to_view_z}
+#endif
+#endif
+
+// Value that helps change animation and synchonize it with other entities outline material
+@group(2) @binding(100)
+var<uniform> u_time : f32;
+// Outline width (power of Fresnel saturation for rim effect)
+@group(2) @binding(101)
+var<uniform> width : f32;
+// Set width change state: (const width)/(time depended)
+@group(2) @binding(102)
+var<uniform> is_time_related : u32;
+// Outline color mixed over the lit surface
+@group(2) @binding(103)
+var<uniform> color : vec4f;
+// Additional outlines blended over base outline in stack order
+@group(2) @binding(104)
+var<uniform> layers : OutlineLayers;
+// Per-camera overrides
+@group(2) @binding(105)
+var<uniform> views : OutlineViews;
+// Camera distance fade, width scaling and cutoff
+@group(2) @binding(106)
+var<uniform> falloff : OutlineFalloff;
+// Rim level multiplier, used for fading outline in and out
+@group(2) @binding(107)
+var<uniform> intensity : f32;
+// Group silhouette parameters
+@group(2) @binding(108)
+var<uniform> silhouette : OutlineSilhouette;
+
+// Rim level for normal, view vector with given width power
+fn rim_level( N : vec3f, V : vec3f, power : f32 ) -> f32
+{
+  var fresnel =  1.0 - max( dot( N, V ), 0.0001 );
+  fresnel = 2.0 * pow( saturate( fresnel ), power );
+  return fresnel;
+}
+
+// Use normal, view vector for rim effect outline level calculation
+fn rim_effect( N : vec3f, V : vec3f ) -> f32
+{
+  return rim_level( N, V, rim_power( width, u_time, is_time_related ) );
+}
+
+// Overrides of currently rendered camera
+fn current_view( ) -> OutlineView
+{
+  return find_view( views, view.world_position, view.viewport );
+}
+
+// Rim multiplier of group silhouette: 1 where some pixel around fragment at `world_position` is much farther
+// than it (silhouette edge), 0 where all are close (seam between group meshes)
+fn silhouette_edge( world_position : vec3f ) -> f32
+{
+  var edge : f32 = 1.0;
+#ifdef OUTLINE_SILHOUETTE
+#ifdef DEPTH_PREPASS
+  let ndc : vec3f = position_world_to_ndc( world_position );
+  let frag_coord : vec2f = view.viewport.xy + ndc_to_uv( ndc.xy ) * view.viewport.zw;
+  let view_z : f32 = depth_ndc_to_view_z( ndc.z );
+  var gap : f32 = 0.0;
+  for ( var i : i32 = 0; i < 8; i++ ) {
+    let angle : f32 = f32( i ) * 0.785398;
+    let offset : vec2f = vec2f( cos( angle ), sin( angle ) ) * silhouette.radius;
+    let coord : vec2f = clamp( frag_coord + offset, view.viewport.xy, view.viewport.xy + view.viewport.zw - 1.0 );
+    let neighbor_z : f32 = depth_ndc_to_view_z( prepass_depth( vec4f( coord, 0.0, 1.0 ), 0u ) );
+    // View z is negative in front of camera, so farther neighbor gives positive gap
+    gap = max( gap, view_z - neighbor_z );
+  }
+  edge = smoothstep( 0.0, silhouette.depth_threshold, gap );
+#endif
+#endif
+  return edge;
+}
+
+// Debug view color of base outline intermediate values, `coverage` is final outline mix amount
+fn debug_color( N : vec3f, V : vec3f, power : f32, coverage : f32 ) -> vec4f
+{
+  var value : vec3f = vec3f( 0.0 );
+#ifdef OUTLINE_DEBUG_FRESNEL
+  value = vec3f( saturate( rim_level( N, V, power ) ) );
+#endif
+#ifdef OUTLINE_DEBUG_NORMAL_DOT_VIEW
+  value = vec3f( saturate( dot( N, V ) ) );
+#endif
+#ifdef OUTLINE_DEBUG_NORMALS
+  value = N * 0.5 + 0.5;
+#endif
+#ifdef OUTLINE_DEBUG_POWER
+  value = vec3f( max( power, 0.0 ) / ( max( power, 0.0 ) + 1.0 ) );
+#endif
+#ifdef OUTLINE_DEBUG_COVERAGE
+  value = vec3f( saturate( coverage ) );
+#endif
+  return vec4f( value, 1.0 );
+}
+
+// Post-lighting hook: mixes outline color and layers over already shaded `base_color` of fragment at `world_position`
+fn apply_rim_effect( base_color : vec4f, N : vec3f, V : vec3f, world_position : vec3f ) -> vec4f
+{
+  let outline_view : OutlineView = current_view( );
+  let distance : f32 = length( world_position - view.world_position );
+  var coverage : f32 = 0.0;
+  var out : vec4f = base_color;
+  let width_scale : f32 = outline_view.width_scale * falloff_width_scale( falloff, distance );
+  let base_power : f32 = rim_power( width, u_time, is_time_related ) / width_scale;
+  if views.enabled != 0u && outline_view.enabled != 0u && !is_cut_off( falloff, distance ) {
+    let level_scale : f32 = intensity * views.intensity * outline_view.intensity * falloff_intensity( falloff, distance )
+      * silhouette_edge( world_position );
+    if ( outline_view.layer_mask & BASE_LAYER_MASK ) != 0u {
+      let level : f32 = rim_level( N, V, base_power ) * level_scale;
+      out = mix( out, color, level );
+      coverage = max( coverage, level );
+    }
+#ifndef OUTLINE_QUALITY_LOW
+    for ( var i : u32 = 0u; i < min( layers.count, MAX_OUTLINE_LAYERS ); i++ ) {
+      if ( outline_view.layer_mask & layer_mask( i ) ) == 0u {
+        continue;
+      }
+      let layer : OutlineLayer = layers.layers[i];
+      let power : f32 = rim_power( layer.width, layer.u_time, layer.is_time_related ) / width_scale;
+      let level : f32 = rim_level( N, V, power ) * level_scale;
+      out = blend_layer( out, layer.color, level, layer.blend );
+      coverage = max( coverage, level );
+    }
+#endif
+  }
+#ifdef OUTLINE_DEBUG
+  out = debug_color( N, V, base_power, coverage );
+#endif
+  return out;
+}
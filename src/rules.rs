@@ -0,0 +1,300 @@
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+use bevy::ecs::world::EntityRef;
+use bevy::prelude::*;
+
+use super::base::OutlineBaseFor;
+use super::outline::{Outline, OutlineLabel};
+use super::plugin::OutlineSystemSet;
+use super::propagation::{OutlineExclude, OutlinePropagation};
+
+/// Component filter of [`OutlineFilter::Component`]: type of component and finder of entities that got it
+#[ derive( Clone, Copy ) ]
+pub struct OutlineComponentFilter
+{
+    type_id : TypeId,
+    name : &'static str,
+    added : fn( &mut World ) -> Vec< Entity >,
+}
+
+impl OutlineComponentFilter
+{
+    /// Filter of entities with `T` component
+    pub fn new< T : Component >( ) -> Self
+    {
+        Self { type_id : TypeId::of::< T >( ), name : std::any::type_name::< T >( ), added : added_entities::< T > }
+    }
+
+    /// Returns type id of filtered component
+    pub fn type_id( &self ) -> TypeId
+    {
+        self.type_id
+    }
+}
+
+impl std::fmt::Debug for OutlineComponentFilter
+{
+    fn fmt( &self, f : &mut std::fmt::Formatter< '_ > ) -> std::fmt::Result
+    {
+        f.debug_tuple( "OutlineComponentFilter" ).field( &self.name ).finish( )
+    }
+}
+
+impl PartialEq for OutlineComponentFilter
+{
+    fn eq( &self, other : &Self ) -> bool
+    {
+        self.type_id == other.type_id
+    }
+}
+
+impl Eq for OutlineComponentFilter { }
+
+/// Returns entities that got `T` since last run of exclusive system that calls it
+fn added_entities< T : Component >( world : &mut World ) -> Vec< Entity >
+{
+    world.query_filtered::< Entity, Added< T > >( ).iter( world ).collect( )
+}
+
+/// Entities matched by [`OutlineRule`]
+#[ derive( Debug, Clone, PartialEq, Eq ) ]
+pub enum OutlineFilter
+{
+    /// Entities whose [`Name`] matches pattern, `*` matches any sequence and `?` any one character
+    Name( String ),
+    /// Entities with component, see [`OutlineFilter::component`]
+    Component( OutlineComponentFilter ),
+}
+
+impl OutlineFilter
+{
+    /// Entities with `T` component
+    pub fn component< T : Component >( ) -> Self
+    {
+        Self::Component( OutlineComponentFilter::new::< T >( ) )
+    }
+
+    /// Returns `true` if `entity` is matched
+    pub fn matches( &self, entity : &EntityRef ) -> bool
+    {
+        match self {
+            Self::Name( pattern ) => entity.get::< Name >( ).is_some_and( | name | wildcard_match( pattern, name.as_str( ) ) ),
+            Self::Component( filter ) => entity.contains_type_id( filter.type_id ),
+        }
+    }
+}
+
+/// What is done with entities matched by [`OutlineRule`]
+#[ derive( Debug, Clone ) ]
+pub enum OutlineRuleAction< O : OutlineLabel >
+{
+    /// Entity and its descendants get outline preset, see [`OutlinePropagation`]
+    Outline( Box< Outline< O > > ),
+    /// Entity and its descendants aren't outlined, see [`OutlineExclude`]
+    Exclude,
+}
+
+/// Rule of [`OutlineRules`]
+#[ derive( Debug, Clone ) ]
+pub struct OutlineRule< O : OutlineLabel >
+{
+    pub filter : OutlineFilter,
+    pub action : OutlineRuleAction< O >,
+}
+
+/// Data-driven O outlines of spawned entities with `B` base material, for example nodes of loaded scenes.
+/// Rules are checked in order when entity gets [`Name`], mesh or component of some component rule,
+/// first matched rule is applied.
+#[ derive( Resource, Debug, Clone ) ]
+pub struct OutlineRules< O : OutlineLabel, B : OutlineBaseFor< O > = StandardMaterial >
+{
+    pub rules : Vec< OutlineRule< O > >,
+    _base : PhantomData< fn( ) -> B >,
+}
+
+impl< O : OutlineLabel, B : OutlineBaseFor< O > > Default for OutlineRules< O, B >
+{
+    fn default( ) -> Self
+    {
+        Self { rules : Vec::new( ), _base : PhantomData }
+    }
+}
+
+impl< O : OutlineLabel, B : OutlineBaseFor< O > > OutlineRules< O, B >
+{
+    /// Entities whose name matches `pattern` get `outline`
+    pub fn name( self, pattern : impl Into< String >, outline : Outline< O > ) -> Self
+    {
+        self.rule( OutlineFilter::Name( pattern.into( ) ), OutlineRuleAction::Outline( Box::new( outline ) ) )
+    }
+
+    /// Entities whose name matches `pattern` aren't outlined
+    pub fn exclude_name( self, pattern : impl Into< String > ) -> Self
+    {
+        self.rule( OutlineFilter::Name( pattern.into( ) ), OutlineRuleAction::Exclude )
+    }
+
+    /// Entities with `T` component get `outline`
+    pub fn component< T : Component >( self, outline : Outline< O > ) -> Self
+    {
+        self.rule( OutlineFilter::component::< T >( ), OutlineRuleAction::Outline( Box::new( outline ) ) )
+    }
+
+    /// Entities with `T` component aren't outlined
+    pub fn exclude_component< T : Component >( self ) -> Self
+    {
+        self.rule( OutlineFilter::component::< T >( ), OutlineRuleAction::Exclude )
+    }
+
+    /// Adds rule after existing ones
+    pub fn rule( mut self, filter : OutlineFilter, action : OutlineRuleAction< O > ) -> Self
+    {
+        self.rules.push( OutlineRule { filter, action } );
+        self
+    }
+
+    /// Returns action of first rule that matches `entity`
+    pub fn action( &self, entity : &EntityRef ) -> Option< &OutlineRuleAction< O > >
+    {
+        self.rules.iter( )
+            .find( | rule | rule.filter.matches( entity ) )
+            .map( | rule | &rule.action )
+    }
+}
+
+/// Applies [`OutlineRules`] of O outline over `B` material, added once by every O outline plugin of `B`
+pub(crate) struct OutlineRulesPlugin< O : OutlineLabel, B : OutlineBaseFor< O > >
+{
+    _outline : PhantomData< fn( ) -> ( O, B ) >,
+}
+
+impl< O : OutlineLabel, B : OutlineBaseFor< O > > Default for OutlineRulesPlugin< O, B >
+{
+    fn default( ) -> Self
+    {
+        Self { _outline : PhantomData }
+    }
+}
+
+impl< O : OutlineLabel, B : OutlineBaseFor< O > > Plugin for OutlineRulesPlugin< O, B >
+{
+    fn build( &self, app : &mut App )
+    {
+        app.add_systems( Update, apply_outline_rules::< O, B >
+            .run_if( resource_exists::< OutlineRules< O, B > > )
+            .in_set( OutlineSystemSet::Prepare ) );
+    }
+}
+
+/// Entities that got name or mesh since last check
+type Appeared = QueryState< Entity, Or< ( Added< Name >, Added< Handle< Mesh > > ) > >;
+
+/// Applies first matched rule to entities that got name, mesh or component of some component rule.
+/// Exclusive, so queries of component rules created at run see changes since its last run.
+pub(crate) fn apply_outline_rules< O : OutlineLabel, B : OutlineBaseFor< O > >( world : &mut World, appeared : &mut Appeared )
+{
+    let components : Vec< OutlineComponentFilter > = world.resource::< OutlineRules< O, B > >( ).rules.iter( )
+        .filter_map( | rule | match &rule.filter {
+            OutlineFilter::Component( filter ) => Some( *filter ),
+            OutlineFilter::Name( _ ) => None,
+        })
+        .collect( );
+    let mut candidates : Vec< Entity > = appeared.iter( world ).collect( );
+    for filter in components {
+        candidates.extend( ( filter.added )( world ) );
+    }
+    candidates.sort_unstable( );
+    candidates.dedup( );
+
+    let rules = world.resource::< OutlineRules< O, B > >( );
+    let actions : Vec< ( Entity, OutlineRuleAction< O > ) > = candidates.into_iter( )
+        .filter_map( | entity | {
+            let action = rules.action( &world.get_entity( entity )? )?;
+            Some( ( entity, action.clone( ) ) )
+        })
+        .collect( );
+    for ( entity, action ) in actions {
+        match action {
+            OutlineRuleAction::Outline( outline ) => {
+                world.entity_mut( entity ).insert( OutlinePropagation::< O, B >::new( *outline ) );
+            }
+            OutlineRuleAction::Exclude => {
+                world.entity_mut( entity ).insert( OutlineExclude );
+            }
+        }
+    }
+}
+
+/// Matches `text` with `pattern`, `*` matches any sequence and `?` any one character
+fn wildcard_match( pattern : &str, text : &str ) -> bool
+{
+    let pattern : Vec< char > = pattern.chars( ).collect( );
+    let text : Vec< char > = text.chars( ).collect( );
+    let ( mut p, mut t ) = ( 0, 0 );
+    let mut backtrack : Option< ( usize, usize ) > = None;
+    while t < text.len( ) {
+        match pattern.get( p ) {
+            Some( '*' ) => {
+                backtrack = Some( ( p, t ) );
+                p += 1;
+            }
+            Some( c ) if *c == '?' || *c == text[ t ] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some( ( star, matched ) ) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some( ( star, matched + 1 ) );
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[ p.. ].iter( ).all( | c | *c == '*' )
+}
+
+#[ cfg( test ) ]
+mod tests
+{
+    use super::*;
+
+    #[ test ]
+    fn star_matches_any_sequence( )
+    {
+        assert!( wildcard_match( "*", "" ) );
+        assert!( wildcard_match( "*", "Door" ) );
+        assert!( wildcard_match( "Door_*", "Door_" ) );
+        assert!( wildcard_match( "Door_*", "Door_Front" ) );
+        assert!( wildcard_match( "*_Hitbox", "Door_Hitbox" ) );
+        assert!( !wildcard_match( "Door_*", "Wall_Door" ) );
+    }
+
+    #[ test ]
+    fn question_mark_matches_one_character( )
+    {
+        assert!( wildcard_match( "Crate_?", "Crate_1" ) );
+        assert!( !wildcard_match( "Crate_?", "Crate_" ) );
+        assert!( !wildcard_match( "Crate_?", "Crate_12" ) );
+        assert!( wildcard_match( "??", "Яю" ) );
+    }
+
+    #[ test ]
+    fn star_backtracks( )
+    {
+        assert!( wildcard_match( "*_LOD?", "Tree_LOD_LOD1" ) );
+        assert!( wildcard_match( "a*b*c", "aXbYbZc" ) );
+        assert!( !wildcard_match( "a*b*c", "aXbYbZ" ) );
+        assert!( wildcard_match( "**x", "abx" ) );
+    }
+
+    #[ test ]
+    fn empty_pattern_matches_only_empty_text( )
+    {
+        assert!( wildcard_match( "", "" ) );
+        assert!( !wildcard_match( "", "Door" ) );
+        assert!( !wildcard_match( "Door", "" ) );
+    }
+}
@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::RenderPipelineDescriptor;
+
+/// Shader def that is set for outline shaders with [`OutlineQuality::Low`]
+pub const QUALITY_LOW_SHADER_DEF : &str = "OUTLINE_QUALITY_LOW";
+
+/// Shader def that is set for outline shaders of [`OutlineSilhouette`](crate::silhouette::OutlineSilhouette) groups
+pub const SILHOUETTE_SHADER_DEF : &str = "OUTLINE_SILHOUETTE";
+
+/// Shader def that is set for outline shaders with any [`OutlineDebugMode`] except `Off`, along with def of mode
+pub const DEBUG_SHADER_DEF : &str = "OUTLINE_DEBUG";
+
+/// Outline rendering quality
+#[ derive( Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash, Default ) ]
+pub enum OutlineQuality
+{
+    /// Only base outline is rendered, stacked layers are skipped
+    Low,
+    /// Base outline and all stacked layers are rendered
+    #[ default ]
+    High,
+}
+
+/// Debug view of outline shaders: fragments show intermediate value of base outline instead of lit color
+#[ derive( Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash, Default ) ]
+pub enum OutlineDebugMode
+{
+    /// Outlines are rendered as is
+    #[ default ]
+    Off,
+    /// Fresnel rim term at current width power, before intensity and falloff
+    Fresnel,
+    /// Dot product of normal and view vector
+    NormalDotView,
+    /// World normal mapped to `0..1` color
+    Normals,
+    /// Animated width power mapped to `power / ( power + 1 )` brightness
+    Power,
+    /// Final outline coverage: how much outline color and layers are mixed over surface.
+    /// It is computed from rim terms only, outlines have no mask texture yet
+    Coverage,
+}
+
+impl OutlineDebugMode
+{
+    /// Shader def that is set for outline shaders in this mode
+    pub fn shader_def( &self ) -> Option< &'static str >
+    {
+        match self {
+            Self::Off => None,
+            Self::Fresnel => Some( "OUTLINE_DEBUG_FRESNEL" ),
+            Self::NormalDotView => Some( "OUTLINE_DEBUG_NORMAL_DOT_VIEW" ),
+            Self::Normals => Some( "OUTLINE_DEBUG_NORMALS" ),
+            Self::Power => Some( "OUTLINE_DEBUG_POWER" ),
+            Self::Coverage => Some( "OUTLINE_DEBUG_COVERAGE" ),
+        }
+    }
+}
+
+/// Global settings of all outlines, for example for graphics options menu.
+/// They are copied to outline materials, so outlines don't need to be touched one by one.
+#[ derive( Resource, Reflect, Debug, Clone, Copy, PartialEq ) ]
+pub struct OutlineSettings
+{
+    /// Master switch: disabled outlines aren't rendered and aren't animated
+    pub enabled : bool,
+    /// Rendering quality, changes shader defs of outline materials
+    pub quality : OutlineQuality,
+    /// Max count of animated materials of every outline material type, nearest to camera are animated first.
+    /// `0` means no limit.
+    pub max_animated : usize,
+    /// Rim level multiplier of all outlines
+    pub intensity : f32,
+    /// Debug view of 3D outline shaders, for example for tuning width
+    pub debug : OutlineDebugMode,
+}
+
+impl OutlineSettings
+{
+    /// Settings with outlines turned off
+    pub fn off( ) -> Self
+    {
+        Self { enabled : false, ..default( ) }
+    }
+
+    /// Settings with outlines at `quality`
+    pub fn with_quality( quality : OutlineQuality ) -> Self
+    {
+        Self { quality, ..default( ) }
+    }
+}
+
+impl Default for OutlineSettings
+{
+    fn default( ) -> Self
+    {
+        Self
+        {
+            enabled : true,
+            quality : OutlineQuality::High,
+            max_animated : 0,
+            intensity : 1.0,
+            debug : OutlineDebugMode::Off,
+        }
+    }
+}
+
+/// Run condition that is `true` while outlines are enabled by [`OutlineSettings::enabled`]
+pub fn outlines_enabled( settings : Res< OutlineSettings > ) -> bool
+{
+    settings.enabled
+}
+
+/// Pipeline key of outline materials
+#[ derive( Debug, Clone, Copy, PartialEq, Eq, Hash ) ]
+pub struct OutlineKey
+{
+    pub quality : OutlineQuality,
+    pub debug : OutlineDebugMode,
+    /// Outline is part of [`OutlineSilhouette`](crate::silhouette::OutlineSilhouette) group
+    pub silhouette : bool,
+}
+
+impl OutlineKey
+{
+    /// Adds shader defs of key to fragment shader of `descriptor`
+    pub fn specialize( &self, descriptor : &mut RenderPipelineDescriptor )
+    {
+        let Some( fragment ) = descriptor.fragment.as_mut( ) else {
+            return;
+        };
+        if self.quality == OutlineQuality::Low {
+            fragment.shader_defs.push( QUALITY_LOW_SHADER_DEF.into( ) );
+        }
+        if self.silhouette {
+            fragment.shader_defs.push( SILHOUETTE_SHADER_DEF.into( ) );
+        }
+        if let Some( def ) = self.debug.shader_def( ) {
+            fragment.shader_defs.push( DEBUG_SHADER_DEF.into( ) );
+            fragment.shader_defs.push( def.into( ) );
+        }
+    }
+}
@@ -0,0 +1,106 @@
+// `ShaderType` derive generates `check` functions that are never called
+#![ allow( dead_code ) ]
+
+use bevy::pbr::MaterialExtension;
+use bevy::prelude::*;
+use bevy::render::render_resource::ShaderType;
+use bevy::utils::HashMap;
+
+use super::base::{OutlineBaseFor, OutlineMaterial};
+use super::commands::{source_base, OutlineMaterialCache};
+use super::outline::{Outline, OutlineLabel};
+
+/// Makes entity and its descendants one outline silhouette: rim is kept only where some pixel around fragment
+/// is much farther than it, so seams where group meshes overlap (for example neck of character) get no rim.
+/// Needs [`DepthPrepass`](bevy::core_pipeline::prepass::DepthPrepass) on camera and opaque or mask base materials,
+/// otherwise outlines stay per mesh. Supported by 3D outlines only, 2D outline materials ignore it.
+///
+/// Depth gap is a heuristic: it doesn't know which meshes belong to group, so rim is suppressed also
+/// where any unrelated object is right behind fragment within `depth_threshold` (for example character leaning on wall),
+/// and thin gaps between group parts that are wider than `depth_threshold` keep their rim.
+#[ derive( Component, Reflect, Debug, Clone, Copy, PartialEq ) ]
+pub struct OutlineSilhouette
+{
+    /// View depth gap to neighbor pixel that makes fragment silhouette edge
+    pub depth_threshold : f32,
+    /// Distance to tested neighbor pixels
+    pub radius : f32,
+}
+
+impl Default for OutlineSilhouette
+{
+    fn default( ) -> Self
+    {
+        Self { depth_threshold : 0.5, radius : 2.0 }
+    }
+}
+
+/// GPU representation of [`OutlineSilhouette`], zero `radius` disables grouping
+#[ derive( ShaderType, Reflect, Debug, Clone, Copy, PartialEq, Default ) ]
+pub struct OutlineSilhouetteParams
+{
+    depth_threshold : f32,
+    radius : f32,
+}
+
+impl OutlineSilhouetteParams
+{
+    /// Returns `true` if outline is part of group silhouette
+    pub fn is_enabled( &self ) -> bool
+    {
+        self.radius > 0.0
+    }
+
+    /// Bits of parameters for outline material cache key
+    pub( crate ) fn key( &self ) -> [ u32; 2 ]
+    {
+        [ self.depth_threshold.to_bits( ), self.radius.to_bits( ) ]
+    }
+}
+
+impl From< &OutlineSilhouette > for OutlineSilhouetteParams
+{
+    fn from( silhouette : &OutlineSilhouette ) -> Self
+    {
+        Self { depth_threshold : silhouette.depth_threshold.max( f32::EPSILON ), radius : silhouette.radius.max( 1.0 ) }
+    }
+}
+
+/// Sets silhouette parameters to O outline materials of every group and resets them for entities left the group.
+/// Materials come from [`OutlineMaterialCache`] with silhouette parameters in key, so entities with same
+/// outline, base and parameters share one material.
+pub(crate) fn apply_silhouettes< O : OutlineLabel, B : OutlineBaseFor< O > >(
+    mut cache : ResMut< OutlineMaterialCache< O, B > >,
+    mut materials : ResMut< Assets< OutlineMaterial< O, B > > >,
+    mut base_materials : ResMut< Assets< B > >,
+    groups : Query< ( Entity, &OutlineSilhouette ) >,
+    children : Query< &Children >,
+    mut outlined : Query< ( Entity, &mut Handle< OutlineMaterial< O, B > > ) >,
+)
+where Outline< O > : MaterialExtension
+{
+    let mut grouped = HashMap::< Entity, OutlineSilhouetteParams >::default( );
+    for ( root, silhouette ) in groups.iter( ) {
+        let params = OutlineSilhouetteParams::from( silhouette );
+        grouped.insert( root, params );
+        for entity in children.iter_descendants( root ) {
+            grouped.insert( entity, params );
+        }
+    }
+
+    for ( entity, mut handle ) in outlined.iter_mut( ) {
+        let params = grouped.get( &entity ).copied( ).unwrap_or_default( );
+        let Some( material ) = materials.get( &*handle ) else {
+            continue;
+        };
+        if material.extension.silhouette == params {
+            continue;
+        }
+        let mut settings = material.extension.clone( );
+        settings.silhouette = params;
+        let base = source_base( material, &mut base_materials );
+        if let Some( material ) = cache.get_or_create( base, settings, &base_materials, &mut materials ) {
+            *handle = material;
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+
+use super::clock::OutlineClock;
+use super::outline::{Outline, OutlineAsset, OutlineLabel};
+use super::transition::{make_unique, OutlineEase};
+
+/// Interaction state of outlined entity, outline is switched to preset of state from [`OutlineStatePresets`]
+#[ derive( Component, Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash, Default ) ]
+pub enum OutlineState
+{
+    #[ default ]
+    Idle,
+    Hovered,
+    Selected,
+    Disabled,
+    Targeted,
+}
+
+/// O outline presets of every [`OutlineState`] and how entity outline moves between them.
+/// State must be kept for `debounce` seconds before outline follows it, so hover flicker is ignored.
+#[ derive( Component, Reflect, Debug, Clone ) ]
+pub struct OutlineStatePresets< O : OutlineLabel >
+{
+    pub idle : Outline< O >,
+    pub hovered : Outline< O >,
+    pub selected : Outline< O >,
+    pub disabled : Outline< O >,
+    pub targeted : Outline< O >,
+    /// Duration of move between presets in seconds
+    pub duration : f32,
+    /// Easing curve of move between presets
+    pub ease : OutlineEase,
+    /// Seconds that state must be kept before outline follows it
+    pub debounce : f32,
+    requested : Option< OutlineState >,
+    requested_for : f32,
+    applied : Option< OutlineState >,
+    from : Option< Outline< O > >,
+    elapsed : f32,
+}
+
+impl< O : OutlineLabel > OutlineStatePresets< O >
+{
+    /// Sets preset of `state`
+    pub fn with( mut self, state : OutlineState, outline : Outline< O > ) -> Self
+    {
+        *self.preset_mut( state ) = outline;
+        self
+    }
+
+    /// Sets duration and easing curve of move between presets
+    pub fn with_transition( mut self, duration : f32, ease : OutlineEase ) -> Self
+    {
+        self.duration = duration;
+        self.ease = ease;
+        self
+    }
+
+    /// Sets debounce time in seconds
+    pub fn with_debounce( mut self, debounce : f32 ) -> Self
+    {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Returns preset of `state`
+    pub fn preset( &self, state : OutlineState ) -> &Outline< O >
+    {
+        match state {
+            OutlineState::Idle => &self.idle,
+            OutlineState::Hovered => &self.hovered,
+            OutlineState::Selected => &self.selected,
+            OutlineState::Disabled => &self.disabled,
+            OutlineState::Targeted => &self.targeted,
+        }
+    }
+
+    /// Returns mutable preset of `state`
+    pub fn preset_mut( &mut self, state : OutlineState ) -> &mut Outline< O >
+    {
+        match state {
+            OutlineState::Idle => &mut self.idle,
+            OutlineState::Hovered => &mut self.hovered,
+            OutlineState::Selected => &mut self.selected,
+            OutlineState::Disabled => &mut self.disabled,
+            OutlineState::Targeted => &mut self.targeted,
+        }
+    }
+
+    /// Returns state whose preset outline shows or moves to
+    pub fn applied( &self ) -> Option< OutlineState >
+    {
+        self.applied
+    }
+
+    /// Tracks requested `state`, returns `true` when outline must start moving to it
+    fn request( &mut self, state : OutlineState, delta_seconds : f32 ) -> bool
+    {
+        if self.requested != Some( state ) {
+            self.requested = Some( state );
+            self.requested_for = 0.0;
+        } else {
+            self.requested_for += delta_seconds;
+        }
+        self.applied != Some( state ) && ( self.applied.is_none( ) || self.requested_for >= self.debounce )
+    }
+}
+
+impl< O : OutlineLabel > Default for OutlineStatePresets< O >
+{
+    fn default( ) -> Self
+    {
+        let hovered = Outline::< O >::default( );
+        let mut hidden = hovered.clone( );
+        hidden.intensity = 0.0;
+        let mut selected = hovered.clone( );
+        selected.color = Color::rgba_linear( 1.0, 0.8, 0.1, 1.0 );
+        selected.is_time_related = 1;
+        let mut targeted = selected.clone( );
+        targeted.color = Color::rgba_linear( 1.0, 0.1, 0.1, 1.0 );
+        targeted.width = 1.0;
+        Self
+        {
+            idle : hidden.clone( ),
+            hovered,
+            selected,
+            disabled : hidden,
+            targeted,
+            duration : 0.15,
+            ease : OutlineEase::QuadraticOut,
+            debounce : 0.05,
+            requested : None,
+            requested_for : 0.0,
+            applied : None,
+            from : None,
+            elapsed : 0.0,
+        }
+    }
+}
+
+/// Moves outline materials of entities with [`OutlineState`] to presets of their states.
+/// First state is applied at once, later ones are eased after debounce time.
+pub(crate) fn update_outline_states< O : OutlineLabel, M : OutlineAsset< O > + Clone >(
+    clock : Res< OutlineClock >,
+    mut materials : ResMut< Assets< M > >,
+    mut query : Query< ( &mut Handle< M >, &OutlineState, &mut OutlineStatePresets< O > ) >,
+)
+{
+    let delta = clock.delta_seconds( );
+    for ( mut handle, state, mut presets ) in query.iter_mut( ) {
+        let presets = &mut *presets;
+        if presets.request( *state, delta ) {
+            make_unique( &mut handle, &mut materials );
+            let Some( material ) = materials.get( &*handle ) else {
+                continue;
+            };
+            presets.from = presets.applied.map( | _ | material.outline( ).clone( ) );
+            presets.applied = Some( *state );
+            presets.elapsed = 0.0;
+            if presets.from.is_none( ) {
+                let preset = presets.preset( *state ).clone( );
+                if let Some( material ) = materials.get_mut( &*handle ) {
+                    material.outline_mut( ).set_parameters( &preset );
+                }
+                continue;
+            }
+        }
+
+        let ( Some( from ), Some( applied ) ) = ( presets.from.as_ref( ), presets.applied ) else {
+            continue;
+        };
+        presets.elapsed += delta;
+        let progress = if presets.duration > 0.0 { ( presets.elapsed / presets.duration ).min( 1.0 ) } else { 1.0 };
+        let outline = from.lerp( presets.preset( applied ), presets.ease.sample( progress ) );
+        if let Some( material ) = materials.get_mut( &*handle ) {
+            material.outline_mut( ).set_parameters( &outline );
+        }
+        if progress >= 1.0 {
+            presets.from = None;
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use super::clock::OutlineClock;
+use super::outline::{OutlineAsset, OutlineLabel};
+use super::transition::make_unique;
+
+/// Puts outline animation of entity into sync group, so it shares group phase instead of own accumulated time.
+/// Entity phase is group time plus `phase_offset` plus projection of entity world position on `world_phase`,
+/// the last one gives traveling waves across rows of objects.
+/// Synced entities get unique outline material, so entities that shared it keep their own time.
+#[ derive( Component, Reflect, Debug, Clone, Copy, PartialEq ) ]
+pub struct OutlineSync
+{
+    /// Sync group id
+    pub group : u32,
+    /// Phase offset in seconds
+    pub phase_offset : f32,
+    /// Phase offset in seconds per world unit along its direction
+    pub world_phase : Vec3,
+}
+
+impl OutlineSync
+{
+    /// Entity in `group` without phase offset
+    pub fn new( group : u32 ) -> Self
+    {
+        Self { group, phase_offset : 0.0, world_phase : Vec3::ZERO }
+    }
+
+    /// Sets phase offset in seconds
+    pub fn with_offset( mut self, phase_offset : f32 ) -> Self
+    {
+        self.phase_offset = phase_offset;
+        self
+    }
+
+    /// Sets phase offset per world unit, wave travels along `world_phase` direction
+    pub fn with_world_phase( mut self, world_phase : Vec3 ) -> Self
+    {
+        self.world_phase = world_phase;
+        self
+    }
+
+    /// Returns `true` if entity phase differs from group phase
+    pub fn has_own_phase( &self ) -> bool
+    {
+        self.phase_offset != 0.0 || self.world_phase != Vec3::ZERO
+    }
+
+    /// Returns entity phase for group time and entity world `position`
+    pub fn phase( &self, group_time : f64, position : Vec3 ) -> f64
+    {
+        group_time + ( self.phase_offset + self.world_phase.dot( position ) ) as f64
+    }
+}
+
+/// Time of outline sync groups. All groups run on one clock and differ by start time.
+/// Time is kept in double precision, so long sessions don't lose phase precision.
+#[ derive( Resource, Debug, Clone, Default ) ]
+pub struct OutlineSyncGroups
+{
+    elapsed : f64,
+    starts : HashMap< u32, f64 >,
+}
+
+impl OutlineSyncGroups
+{
+    /// Returns time of `group` in seconds
+    pub fn time( &self, group : u32 ) -> f64
+    {
+        self.elapsed - self.starts.get( &group ).copied( ).unwrap_or( 0.0 )
+    }
+
+    /// Sets time of `group`, so phase of its entities jumps to it
+    pub fn set_time( &mut self, group : u32, time : f64 )
+    {
+        self.starts.insert( group, self.elapsed - time );
+    }
+
+    /// Restarts `group` from zero time
+    pub fn reset( &mut self, group : u32 )
+    {
+        self.set_time( group, 0.0 );
+    }
+
+    /// Advances clock of all groups
+    pub fn advance( &mut self, delta_seconds : f32 )
+    {
+        self.elapsed += delta_seconds as f64;
+    }
+}
+
+/// Advances clock of sync groups
+pub(crate) fn advance_sync_groups( clock : Res< OutlineClock >, mut groups : ResMut< OutlineSyncGroups > )
+{
+    groups.advance( clock.delta_seconds( ) );
+}
+
+/// Sets animation time of outline materials of synced entities from their group phase
+pub(crate) fn update_synced_outlines< O : OutlineLabel, M : OutlineAsset< O > + Clone >(
+    groups : Res< OutlineSyncGroups >,
+    mut materials : ResMut< Assets< M > >,
+    mut query : Query< ( &mut Handle< M >, &OutlineSync, &GlobalTransform ) >,
+)
+{
+    for ( mut handle, sync, transform ) in query.iter_mut( ) {
+        make_unique( &mut handle, &mut materials );
+        let phase = sync.phase( groups.time( sync.group ), transform.translation( ) );
+        if let Some( material ) = materials.get_mut( &*handle ) {
+            material.outline_mut( ).set_phase( phase );
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use bevy::ecs::system::CommandQueue;
+use bevy::pbr::{MaterialExtension, MaterialPlugin};
+use bevy::prelude::*;
+
+use super::base::{OutlineBase, OutlineBaseFor, OutlineMaterial};
+use super::clock::{OutlineClock, OutlineTimeSource};
+use super::outline::{Outline, OutlineLabel};
+use super::plugin::OutlinePlugin;
+
+/// Headless app with O outline over [`StandardMaterial`] for tests of outline systems, see [`outline_test_app_on`]
+pub fn outline_test_app< O : OutlineLabel >( ) -> App
+where
+    Outline< O > : MaterialExtension,
+    StandardMaterial : OutlineBaseFor< O >,
+    MaterialPlugin::< OutlineMaterial< O > > : Plugin
+{
+    outline_test_app_on::< O, StandardMaterial >( )
+}
+
+/// Headless app with O outline over `B` material for tests of outline systems:
+/// [`MinimalPlugins`], assets, hierarchy and transforms, but no window and render plugins, so no GPU is needed.
+/// Outline clock has [`OutlineTimeSource::Custom`] source, so outlines are animated only by [`OutlineTestExt::step`].
+pub fn outline_test_app_on< O : OutlineLabel, B : OutlineBaseFor< O > >( ) -> App
+where
+    Outline< O > : MaterialExtension,
+    MaterialPlugin::< OutlineMaterial< O, B > > : Plugin
+{
+    let mut app = App::new( );
+    app.add_plugins( ( MinimalPlugins, AssetPlugin::default( ), HierarchyPlugin, TransformPlugin ) )
+        .init_asset::< Shader >( )
+        .init_asset::< Mesh >( )
+        .init_asset::< B >( )
+        .insert_resource( OutlineClock::new( OutlineTimeSource::Custom ) )
+        .add_plugins( OutlinePlugin::< O, B >::default( ) );
+    app
+}
+
+/// Helpers of outline test app, see [`outline_test_app`]
+pub trait OutlineTestExt
+{
+    /// Advances outline clock by `seconds` and runs one app update
+    fn step( &mut self, seconds : f32 ) -> &mut Self;
+
+    /// Runs commands built by `build` at once, for example [`OutlineCommandsExt`](crate::commands::OutlineCommandsExt) ones
+    fn run_commands( &mut self, build : impl FnOnce( Commands ) ) -> &mut Self;
+
+    /// Spawns entity with `material` added to assets and default transform
+    fn spawn_base< B : OutlineBase >( &mut self, material : B ) -> Entity;
+
+    /// Returns O outline of entity material over [`StandardMaterial`]
+    fn outline< O : OutlineLabel >( &self, entity : Entity ) -> Option< &Outline< O > >
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >;
+
+    /// Returns O outline of entity material over `B` material
+    fn outline_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &self, entity : Entity ) -> Option< &Outline< O > >
+    where Outline< O > : MaterialExtension;
+}
+
+impl OutlineTestExt for App
+{
+    fn step( &mut self, seconds : f32 ) -> &mut Self
+    {
+        self.world.resource_mut::< OutlineClock >( ).advance_by( seconds );
+        self.update( );
+        self
+    }
+
+    fn run_commands( &mut self, build : impl FnOnce( Commands ) ) -> &mut Self
+    {
+        let mut queue = CommandQueue::default( );
+        build( Commands::new( &mut queue, &self.world ) );
+        queue.apply( &mut self.world );
+        self
+    }
+
+    fn spawn_base< B : OutlineBase >( &mut self, material : B ) -> Entity
+    {
+        let material = self.world.resource_mut::< Assets< B > >( ).add( material );
+        self.world.spawn( ( material, TransformBundle::default( ) ) ).id( )
+    }
+
+    fn outline< O : OutlineLabel >( &self, entity : Entity ) -> Option< &Outline< O > >
+    where Outline< O > : MaterialExtension, StandardMaterial : OutlineBaseFor< O >
+    {
+        self.outline_on::< O, StandardMaterial >( entity )
+    }
+
+    fn outline_on< O : OutlineLabel, B : OutlineBaseFor< O > >( &self, entity : Entity ) -> Option< &Outline< O > >
+    where Outline< O > : MaterialExtension
+    {
+        let handle = self.world.get::< Handle< OutlineMaterial< O, B > > >( entity )?;
+        self.world.resource::< Assets< OutlineMaterial< O, B > > >( )
+            .get( handle )
+            .map( | material | &material.extension )
+    }
+}
@@ -0,0 +1,244 @@
+use std::f32::consts::PI;
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use super::clock::OutlineClock;
+use super::outline::{Outline, OutlineAsset, OutlineLabel};
+
+/// Easing curve of outline transitions
+#[ derive( Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash, Default ) ]
+pub enum OutlineEase
+{
+    #[ default ]
+    Linear,
+    QuadraticIn,
+    QuadraticOut,
+    QuadraticInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+}
+
+impl OutlineEase
+{
+    /// Returns eased progress for linear progress `t` in `[0, 1]`
+    pub fn sample( self, t : f32 ) -> f32
+    {
+        let t = t.clamp( 0.0, 1.0 );
+        match self {
+            Self::Linear => t,
+            Self::QuadraticIn => t * t,
+            Self::QuadraticOut => 1.0 - ( 1.0 - t ) * ( 1.0 - t ),
+            Self::QuadraticInOut => if t < 0.5 { 2.0 * t * t } else { 1.0 - ( -2.0 * t + 2.0 ).powi( 2 ) / 2.0 },
+            Self::CubicIn => t * t * t,
+            Self::CubicOut => 1.0 - ( 1.0 - t ).powi( 3 ),
+            Self::CubicInOut => if t < 0.5 { 4.0 * t * t * t } else { 1.0 - ( -2.0 * t + 2.0 ).powi( 3 ) / 2.0 },
+            Self::SineIn => 1.0 - ( t * PI / 2.0 ).cos( ),
+            Self::SineOut => ( t * PI / 2.0 ).sin( ),
+            Self::SineInOut => -( ( PI * t ).cos( ) - 1.0 ) / 2.0,
+        }
+    }
+}
+
+/// Outline parameters animated by transition, `None` parameters are kept
+#[ derive( Reflect, Debug, Clone, Copy, PartialEq, Default ) ]
+pub struct OutlineTargets
+{
+    pub intensity : Option< f32 >,
+    pub width : Option< f32 >,
+    pub color : Option< Color >,
+}
+
+impl OutlineTargets
+{
+    /// Takes values of parameters set in `self` from `outline`
+    fn capture< O : OutlineLabel >( &self, outline : &Outline< O > ) -> Self
+    {
+        Self
+        {
+            intensity : self.intensity.map( | _ | outline.intensity ),
+            width : self.width.map( | _ | outline.width ),
+            color : self.color.map( | _ | outline.color ),
+        }
+    }
+}
+
+/// Animates O outline parameters of entity to `to` over `duration` seconds with `ease` curve.
+/// Entity outline material is made unique first, so entities that shared it aren't affected.
+/// Component is removed and [`OutlineTransitionFinished`] is sent when transition is done.
+#[ derive( Component, Reflect, Debug, Clone ) ]
+pub struct OutlineTransition< O : OutlineLabel >
+{
+    /// Start values, current outline values are used for parameters that are `None`
+    pub from : OutlineTargets,
+    /// Target values
+    pub to : OutlineTargets,
+    /// Transition duration in seconds
+    pub duration : f32,
+    /// Easing curve
+    pub ease : OutlineEase,
+    elapsed : f32,
+    started : bool,
+    #[ reflect( ignore ) ]
+    _outline : PhantomData< fn( ) -> O >,
+}
+
+impl< O : OutlineLabel > OutlineTransition< O >
+{
+    /// Transition to `to` values over `duration` seconds
+    pub fn new( to : OutlineTargets, duration : f32 ) -> Self
+    {
+        Self
+        {
+            from : OutlineTargets::default( ),
+            to,
+            duration,
+            ease : OutlineEase::default( ),
+            elapsed : 0.0,
+            started : false,
+            _outline : PhantomData,
+        }
+    }
+
+    /// Transition of intensity to `intensity`
+    pub fn intensity( intensity : f32, duration : f32 ) -> Self
+    {
+        Self::new( OutlineTargets { intensity : Some( intensity ), ..default( ) }, duration )
+    }
+
+    /// Transition of width to `width`
+    pub fn width( width : f32, duration : f32 ) -> Self
+    {
+        Self::new( OutlineTargets { width : Some( width ), ..default( ) }, duration )
+    }
+
+    /// Transition of color to `color`
+    pub fn color( color : Color, duration : f32 ) -> Self
+    {
+        Self::new( OutlineTargets { color : Some( color ), ..default( ) }, duration )
+    }
+
+    /// Intensity transition from `0.0` to `1.0`
+    pub fn fade_in( duration : f32 ) -> Self
+    {
+        Self::intensity( 1.0, duration ).from( OutlineTargets { intensity : Some( 0.0 ), ..default( ) } )
+            .with_ease( OutlineEase::QuadraticOut )
+    }
+
+    /// Intensity transition to `0.0`
+    pub fn fade_out( duration : f32 ) -> Self
+    {
+        Self::intensity( 0.0, duration ).with_ease( OutlineEase::QuadraticIn )
+    }
+
+    /// Sets start values
+    pub fn from( mut self, from : OutlineTargets ) -> Self
+    {
+        self.from = from;
+        self
+    }
+
+    /// Sets easing curve
+    pub fn with_ease( mut self, ease : OutlineEase ) -> Self
+    {
+        self.ease = ease;
+        self
+    }
+
+    /// Returns linear progress in `[0, 1]`
+    pub fn progress( &self ) -> f32
+    {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        ( self.elapsed / self.duration ).clamp( 0.0, 1.0 )
+    }
+
+    /// Writes interpolated values for current progress to `outline`
+    fn apply( &self, outline : &mut Outline< O > )
+    {
+        let t = self.ease.sample( self.progress( ) );
+        if let ( Some( from ), Some( to ) ) = ( self.from.intensity, self.to.intensity ) {
+            outline.intensity = from + ( to - from ) * t;
+        }
+        if let ( Some( from ), Some( to ) ) = ( self.from.width, self.to.width ) {
+            outline.width = from + ( to - from ) * t;
+        }
+        if let ( Some( from ), Some( to ) ) = ( self.from.color, self.to.color ) {
+            outline.color = lerp_color( from, to, t );
+        }
+    }
+}
+
+/// Interpolates colors in linear space
+pub(crate) fn lerp_color( from : Color, to : Color, t : f32 ) -> Color
+{
+    let from = Vec4::from( from.as_linear_rgba_f32( ) );
+    let to = Vec4::from( to.as_linear_rgba_f32( ) );
+    let [ r, g, b, a ] = from.lerp( to, t ).to_array( );
+    Color::rgba_linear( r, g, b, a )
+}
+
+/// Sent when [`OutlineTransition`] of `entity` is done
+#[ derive( Event, Debug, Clone, Copy, PartialEq, Eq ) ]
+pub struct OutlineTransitionFinished
+{
+    pub entity : Entity,
+}
+
+/// Makes material of `handle` used only by its owner: clones asset if handle is shared
+pub(crate) fn make_unique< M : Asset + Clone >( handle : &mut Handle< M >, materials : &mut Assets< M > )
+{
+    let shared = match &*handle {
+        Handle::Strong( strong ) => std::sync::Arc::strong_count( strong ) > 1,
+        Handle::Weak( _ ) => true,
+    };
+    if !shared {
+        return;
+    }
+    if let Some( material ) = materials.get( &*handle ).cloned( ) {
+        *handle = materials.add( material );
+    }
+}
+
+/// Advances [`OutlineTransition`]s of entities with `M` material
+pub(crate) fn update_outline_transitions< O : OutlineLabel, M : OutlineAsset< O > + Clone >(
+    mut commands : Commands,
+    clock : Res< OutlineClock >,
+    mut materials : ResMut< Assets< M > >,
+    mut query : Query< ( Entity, &mut Handle< M >, &mut OutlineTransition< O > ) >,
+    mut finished : EventWriter< OutlineTransitionFinished >,
+)
+{
+    for ( entity, mut handle, mut transition ) in query.iter_mut( ) {
+        if !transition.started {
+            make_unique( &mut handle, &mut materials );
+            let Some( material ) = materials.get( &*handle ) else {
+                continue;
+            };
+            let current = transition.to.capture( material.outline( ) );
+            transition.from = OutlineTargets
+            {
+                intensity : transition.from.intensity.or( current.intensity ),
+                width : transition.from.width.or( current.width ),
+                color : transition.from.color.or( current.color ),
+            };
+            transition.started = true;
+        } else {
+            transition.elapsed += clock.delta_seconds( );
+        }
+
+        let Some( material ) = materials.get_mut( &*handle ) else {
+            continue;
+        };
+        transition.apply( material.outline_mut( ) );
+        if transition.progress( ) >= 1.0 {
+            commands.entity( entity ).remove::< OutlineTransition< O > >( );
+            finished.send( OutlineTransitionFinished { entity } );
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// `ShaderType` derive generates `check` functions that are never called
+#![ allow( dead_code ) ]
+
+use bevy::prelude::*;
+use bevy::render::render_resource::ShaderType;
+
+use super::outline::{OutlineAsset, OutlineLabel};
+use super::settings::OutlineSettings;
+
+/// Max count of cameras with [`OutlineViewSettings`]
+pub const MAX_OUTLINE_VIEWS : usize = 4;
+
+/// Per-camera outline overrides. Cameras without it render outlines as is.
+///
+/// Material shaders can't get per-camera data, so settings are copied to every outline material
+/// and matched in shader by camera position and viewport of current frame. Cameras at the same position
+/// with the same viewport can't be told apart, the first of them gives settings to all, so they must share settings.
+#[ derive( Component, Reflect, Debug, Clone, Copy, PartialEq ) ]
+pub struct OutlineViewSettings
+{
+    /// Outlines are rendered in this view
+    pub enabled : bool,
+    /// Rim width multiplier (width power is divided by it, so greater value gives wider rim)
+    pub width_scale : f32,
+    /// Rim level multiplier
+    pub intensity : f32,
+    /// Bit mask of rendered outline stack layers: [`OutlineViewSettings::BASE_LAYER`] and [`OutlineViewSettings::layer`]
+    pub layer_mask : u32,
+}
+
+impl OutlineViewSettings
+{
+    /// Mask of base outline
+    pub const BASE_LAYER : u32 = 1;
+    /// Mask of base outline and all stacked layers
+    pub const ALL_LAYERS : u32 = u32::MAX;
+
+    /// Mask of stacked [`OutlineLayer`](crate::layers::OutlineLayer) at `index`
+    pub const fn layer( index : usize ) -> u32
+    {
+        1 << ( index + 1 )
+    }
+
+    /// Settings that hide outlines in view
+    pub fn disabled( ) -> Self
+    {
+        Self { enabled : false, ..default( ) }
+    }
+}
+
+impl Default for OutlineViewSettings
+{
+    fn default( ) -> Self
+    {
+        Self
+        {
+            enabled : true,
+            width_scale : 1.0,
+            intensity : 1.0,
+            layer_mask : Self::ALL_LAYERS,
+        }
+    }
+}
+
+/// GPU representation of [`OutlineViewSettings`]
+#[ derive( ShaderType, Reflect, Debug, Clone, Copy, PartialEq, Default ) ]
+pub struct OutlineView
+{
+    /// Camera physical viewport (x, y, width, height)
+    viewport : Vec4,
+    /// Camera world position
+    world_position : Vec3,
+    width_scale : f32,
+    intensity : f32,
+    layer_mask : u32,
+    enabled : u32,
+}
+
+/// Settings of every camera with [`OutlineViewSettings`] and global [`OutlineSettings`], copied to outline materials
+#[ derive( Resource, ShaderType, Reflect, Debug, Clone, Copy, PartialEq ) ]
+pub struct OutlineViews
+{
+    views : [ OutlineView; MAX_OUTLINE_VIEWS ],
+    count : u32,
+    /// Global rim level multiplier
+    intensity : f32,
+    /// Global switch
+    enabled : u32,
+}
+
+impl Default for OutlineViews
+{
+    fn default( ) -> Self
+    {
+        Self
+        {
+            views : [ OutlineView::default( ); MAX_OUTLINE_VIEWS ],
+            count : 0,
+            intensity : 1.0,
+            enabled : 1,
+        }
+    }
+}
+
+/// Collects [`OutlineViews`] from active cameras and global settings, runs after transform propagation
+pub(crate) fn collect_view_settings(
+    cameras : Query< ( &Camera, &GlobalTransform, &OutlineViewSettings ) >,
+    settings : Res< OutlineSettings >,
+    mut views : ResMut< OutlineViews >,
+)
+{
+    let mut collected = OutlineViews
+    {
+        intensity : settings.intensity,
+        enabled : settings.enabled as u32,
+        ..default( )
+    };
+    for ( camera, transform, settings ) in cameras.iter( ).filter( | ( camera, _, _ ) | camera.is_active ) {
+        if collected.count as usize == MAX_OUTLINE_VIEWS {
+            warn!( "More than {} cameras with OutlineViewSettings, rest are ignored", MAX_OUTLINE_VIEWS );
+            break;
+        }
+        let Some( viewport ) = camera.physical_viewport_rect( ) else {
+            continue;
+        };
+        collected.views[ collected.count as usize ] = OutlineView
+        {
+            viewport : Vec4::new( viewport.min.x as f32, viewport.min.y as f32, viewport.width( ) as f32, viewport.height( ) as f32 ),
+            world_position : transform.translation( ),
+            width_scale : settings.width_scale.max( f32::EPSILON ),
+            intensity : settings.intensity,
+            layer_mask : settings.layer_mask,
+            enabled : settings.enabled as u32,
+        };
+        collected.count += 1;
+    }
+    views.set_if_neq( collected );
+}
+
+/// Copies [`OutlineViews`], quality and debug mode to O outline materials that have outdated copy
+pub(crate) fn apply_view_settings< O : OutlineLabel, M : OutlineAsset< O > >(
+    views : Res< OutlineViews >,
+    settings : Res< OutlineSettings >,
+    mut materials : ResMut< Assets< M > >,
+)
+{
+    let outdated : Vec< AssetId< M > > = materials.iter( )
+        .filter( | ( _, material ) | {
+            let outline = material.outline( );
+            outline.views != *views || outline.quality != settings.quality || outline.debug != settings.debug
+        })
+        .map( | ( id, _ ) | id )
+        .collect( );
+    for id in outdated {
+        if let Some( material ) = materials.get_mut( id ) {
+            let outline = material.outline_mut( );
+            outline.views = *views;
+            outline.quality = settings.quality;
+            outline.debug = settings.debug;
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::{asset::load_internal_asset, prelude::*};
+use bevy::pbr::{MaterialPipeline, MaterialPipelineKey, NotShadowCaster};
+use bevy::render::mesh::MeshVertexBufferLayout;
+use bevy::render::render_resource::{
+    AsBindGroup, CompareFunction, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+};
+
+pub const XRAY_SHADER_HANDLE : Handle< Shader > =
+    Handle::< Shader >::weak_from_u128( 42572727525244273578 );
+
+/// Fill pattern of occluded part of entity
+#[ derive( Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash, Default ) ]
+pub enum XRayPattern
+{
+    /// Whole silhouette is filled
+    #[ default ]
+    Solid,
+    /// Diagonal screen space stripes
+    Stripes,
+    /// Screen space dots grid
+    Dots,
+}
+
+/// Makes entity silhouette visible through occluders with `color` and `pattern`.
+/// Unoccluded part keeps its own material (for example rim effect outline).
+#[ derive( Component, Reflect, Debug, Clone, Copy, PartialEq ) ]
+pub struct OutlineXRay
+{
+    /// Color of occluded part
+    pub color : Color,
+    /// Fill pattern of occluded part
+    pub pattern : XRayPattern,
+    /// Pattern period in pixels
+    pub pattern_scale : f32,
+}
+
+impl OutlineXRay
+{
+    /// Occluded part filled with `color`
+    pub fn new( color : Color ) -> Self
+    {
+        Self { color, ..default( ) }
+    }
+}
+
+impl Default for OutlineXRay
+{
+    fn default( ) -> Self
+    {
+        Self
+        {
+            color : Color::rgba_linear( 0.6, 0.6, 0.0, 0.5 ),
+            pattern : XRayPattern::Solid,
+            pattern_scale : 8.0,
+        }
+    }
+}
+
+/// Material of occluded silhouette, drawn in transparent pass with reversed depth test
+#[ derive( Asset, AsBindGroup, Reflect, Debug, Clone ) ]
+pub struct OutlineXRayMaterial
+{
+    /// Color of occluded part
+    #[ uniform( 0 ) ]
+    pub color : Color,
+    /// [`XRayPattern`] index
+    #[ uniform( 1 ) ]
+    pub pattern : u32,
+    /// Pattern period in pixels
+    #[ uniform( 2 ) ]
+    pub pattern_scale : f32,
+}
+
+impl From< &OutlineXRay > for OutlineXRayMaterial
+{
+    fn from( xray : &OutlineXRay ) -> Self
+    {
+        Self
+        {
+            color : xray.color,
+            pattern : xray.pattern as u32,
+            pattern_scale : xray.pattern_scale.max( 1.0 ),
+        }
+    }
+}
+
+impl Material for OutlineXRayMaterial
+{
+    fn fragment_shader( ) -> ShaderRef
+    {
+        XRAY_SHADER_HANDLE.into( )
+    }
+
+    fn alpha_mode( &self ) -> AlphaMode
+    {
+        AlphaMode::Blend
+    }
+
+    fn specialize(
+        _pipeline : &MaterialPipeline< Self >,
+        descriptor : &mut RenderPipelineDescriptor,
+        _layout : &MeshVertexBufferLayout,
+        _key : MaterialPipelineKey< Self >,
+    ) -> Result< (), SpecializedMeshPipelineError >
+    {
+        // Depth is reversed (near is 1.0), so fragment is occluded when its depth is less than stored one
+        if let Some( depth_stencil ) = descriptor.depth_stencil.as_mut( ) {
+            depth_stencil.depth_compare = CompareFunction::Less;
+            depth_stencil.depth_write_enabled = false;
+        }
+        Ok( () )
+    }
+}
+
+/// Entities with new [`OutlineXRay`] that have no proxy yet
+type NewXRays< 'w, 's > = Query< 'w, 's, ( Entity, &'static OutlineXRay, &'static Handle< Mesh > ), ( Added< OutlineXRay >, Without< XRayProxy > ) >;
+
+/// Entities whose [`OutlineXRay`] or mesh is changed
+type ChangedXRays< 'w, 's > = Query< 'w, 's, ( &'static OutlineXRay, &'static Handle< Mesh >, &'static XRayProxy ), Or< ( Changed< OutlineXRay >, Changed< Handle< Mesh > > ) > >;
+
+/// Child entity that draws occluded silhouette of its parent
+#[ derive( Component ) ]
+struct XRayProxy
+{
+    entity : Entity,
+    material : Handle< OutlineXRayMaterial >,
+}
+
+/// Adds [`OutlineXRay`] support: extra pass that draws occluded silhouettes of entities
+pub struct OutlineXRayPlugin;
+
+impl Plugin for OutlineXRayPlugin
+{
+    fn build( &self, app : &mut App )
+    {
+        load_internal_asset!( app, XRAY_SHADER_HANDLE, "xray.wgsl", Shader::from_wgsl );
+
+        app.add_plugins( MaterialPlugin::< OutlineXRayMaterial >::default( ) )
+            .register_type::< OutlineXRay >( )
+            .add_systems( Update, (
+                remove_xray_proxies,
+                spawn_xray_proxies,
+                update_xray_proxies,
+            ).chain( ) );
+    }
+}
+
+/// Spawns proxy child for every new [`OutlineXRay`]
+fn spawn_xray_proxies(
+    mut commands : Commands,
+    mut materials : ResMut< Assets< OutlineXRayMaterial > >,
+    query : NewXRays,
+)
+{
+    for ( entity, xray, mesh ) in query.iter( ) {
+        let material = materials.add( OutlineXRayMaterial::from( xray ) );
+        let proxy = commands
+            .spawn( (
+                MaterialMeshBundle {
+                    mesh : mesh.clone( ),
+                    material : material.clone( ),
+                    ..default( )
+                },
+                NotShadowCaster,
+            ))
+            .set_parent( entity )
+            .id( );
+        commands.entity( entity ).insert( XRayProxy { entity : proxy, material } );
+    }
+}
+
+/// Keeps proxy material and mesh in sync with its parent
+fn update_xray_proxies(
+    mut materials : ResMut< Assets< OutlineXRayMaterial > >,
+    query : ChangedXRays,
+    mut meshes : Query< &mut Handle< Mesh >, Without< XRayProxy > >,
+)
+{
+    for ( xray, mesh, proxy ) in query.iter( ) {
+        if let Some( material ) = materials.get_mut( &proxy.material ) {
+            *material = OutlineXRayMaterial::from( xray );
+        }
+        if let Ok( mut proxy_mesh ) = meshes.get_mut( proxy.entity ) {
+            if *proxy_mesh != *mesh {
+                *proxy_mesh = mesh.clone( );
+            }
+        }
+    }
+}
+
+/// Despawns proxy when [`OutlineXRay`] is removed
+fn remove_xray_proxies(
+    mut commands : Commands,
+    mut removed : RemovedComponents< OutlineXRay >,
+    proxies : Query< &XRayProxy >,
+)
+{
+    for entity in removed.read( ) {
+        let Ok( proxy ) = proxies.get( entity ) else {
+            continue;
+        };
+        if let Some( proxy_entity ) = commands.get_entity( proxy.entity ) {
+            proxy_entity.despawn_recursive( );
+        }
+        commands.entity( entity ).remove::< XRayProxy >( );
+    }
+}
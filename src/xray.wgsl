@@ -0,0 +1,43 @@
+#import bevy_pbr::{
+  forward_io::VertexOutput,
+  mesh_view_bindings::view,
+}
+
+// Color of occluded part
+@group(2) @binding(0)
+var<uniform> color : vec4f;
+// Fill pattern: 0 - solid, 1 - stripes, 2 - dots
+@group(2) @binding(1)
+var<uniform> pattern : u32;
+// Pattern period in pixels
+@group(2) @binding(2)
+var<uniform> pattern_scale : f32;
+
+const PATTERN_STRIPES : u32 = 1u;
+const PATTERN_DOTS : u32 = 2u;
+
+// Coverage of pattern at screen `position`
+fn pattern_mask( position : vec2f ) -> f32
+{
+  if pattern == PATTERN_STRIPES {
+    return step( 0.5, fract( ( position.x + position.y ) / pattern_scale ) );
+  }
+  if pattern == PATTERN_DOTS {
+    let cell : vec2f = fract( position / pattern_scale ) - 0.5;
+    return step( length( cell ), 0.25 );
+  }
+  return 1.0;
+}
+
+// Occluded silhouette: pattern filled and brightened at silhouette edge
+@fragment
+fn fragment(
+  in : VertexOutput,
+) -> @location(0) vec4f
+{
+  let N : vec3f = normalize( in.world_normal );
+  let V : vec3f = normalize( view.world_position - in.world_position.xyz );
+  let fresnel : f32 = 1.0 - max( dot( N, V ), 0.0 );
+  let alpha : f32 = color.a * max( pattern_mask( in.position.xy ), fresnel );
+  return vec4f( color.rgb, saturate( alpha ) );
+}
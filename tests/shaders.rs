@@ -0,0 +1,498 @@
+//! Composes every outline shader with shader def combinations through `naga_oil` and validates it with `naga`,
+//! so broken WGSL fails `cargo test` instead of pipeline creation. No GPU is needed: shader modules are taken
+//! from headless app without renderer.
+//!
+//! Animation and blend modes are uniforms, so every combination covers all of them.
+//! Prepass and deferred passes use base material shaders, outline shaders are validated in main forward pass
+//! with and without prepass textures. Outlines have no deferred fragment shader, so deferred opaque entities
+//! are drawn without rim, see [`outlines::base::OutlineBase`].
+//!
+//! Headless app is built once and its shaders are shared by all tests.
+//!
+//! Rim math of composed shaders is also evaluated on CPU by small interpreter of naga IR
+//! and compared with its Rust mirror in [`outlines::rim_effect`].
+
+use std::sync::OnceLock;
+
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Shader, ShaderImport};
+use bevy::render::settings::{RenderCreation, WgpuSettings};
+use bevy::render::RenderPlugin;
+use bevy::utils::HashMap;
+use bevy::winit::WinitPlugin;
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use naga::{BinaryOperator, Expression, Literal, MathFunction, Module, Statement};
+use naga_oil::compose::{Composer, NagaModuleDescriptor, ShaderDefValue};
+
+use outlines::outline_2d::OutlinePlugin2d;
+use outlines::plugin::OutlinePlugin;
+use outlines::rim_effect::{rim_level, rim_power, RimEffect, RimEffectHook, HOOK_SHADER_DEF, SHADER_2D_HANDLE, SHADER_HANDLE};
+use outlines::settings::{OutlineDebugMode, DEBUG_SHADER_DEF, QUALITY_LOW_SHADER_DEF, SILHOUETTE_SHADER_DEF};
+use outlines::xray::{OutlineXRayPlugin, XRAY_SHADER_HANDLE};
+
+/// Base material fragment shader that applies outline by [`RimEffectHook`]
+const HOOK_SHADER : &str = r#"
+#import bevy_pbr::forward_io::VertexOutput
+#ifdef RIM_EFFECT_HOOK
+#import outlines::rim_effect_functions::apply_rim_effect
+#endif
+
+@fragment
+fn fragment( in : VertexOutput ) -> @location(0) vec4f
+{
+  var color : vec4f = vec4f( 1.0 );
+#ifdef RIM_EFFECT_HOOK
+  color = apply_rim_effect( color, normalize( in.world_normal ), vec3f( 0.0, 0.0, 1.0 ), in.world_position.xyz );
+#endif
+  return color;
+}
+"#;
+
+/// Shader that imports rim math functions, so they are kept in composed module
+const RIM_MATH_SHADER : &str = r#"
+#import outlines::outline_types::rim_power
+#import outlines::rim_effect_functions::rim_level
+
+@fragment
+fn fragment( ) -> @location(0) vec4f
+{
+  return vec4f( rim_level( vec3f( 0.0, 0.0, 1.0 ), vec3f( 0.0, 0.0, 1.0 ), rim_power( 2.0, 0.0, 0u ) ) );
+}
+"#;
+
+/// Shader defs that mesh pipelines and pipeline cache always set
+const PIPELINE_SHADER_DEFS : [ ( &str, ShaderDefValue ); 8 ] = [
+    ( "VERTEX_POSITIONS", ShaderDefValue::Bool( true ) ),
+    ( "VERTEX_NORMALS", ShaderDefValue::Bool( true ) ),
+    ( "VERTEX_UVS", ShaderDefValue::Bool( true ) ),
+    ( "VERTEX_OUTPUT_INSTANCE_INDEX", ShaderDefValue::Bool( true ) ),
+    ( "MAX_DIRECTIONAL_LIGHTS", ShaderDefValue::UInt( 10 ) ),
+    ( "MAX_CASCADES_PER_LIGHT", ShaderDefValue::UInt( 4 ) ),
+    ( "AVAILABLE_STORAGE_BUFFER_BINDINGS", ShaderDefValue::UInt( 8 ) ),
+    ( "PER_OBJECT_BUFFER_BATCH_SIZE", ShaderDefValue::UInt( 1 ) ),
+];
+
+/// Shader defs of main pass views: without prepass, with depth prepass, with all prepasses and multisampled
+const VIEW_SHADER_DEFS : [ &[ &str ]; 4 ] = [
+    &[ ],
+    &[ "DEPTH_PREPASS" ],
+    &[ "DEPTH_PREPASS", "NORMAL_PREPASS", "MOTION_VECTOR_PREPASS" ],
+    &[ "DEPTH_PREPASS", "NORMAL_PREPASS", "MULTISAMPLED" ],
+];
+
+/// Shader defs of mesh layouts
+const MESH_SHADER_DEFS : [ &[ &str ]; 2 ] = [
+    &[ ],
+    &[ "VERTEX_TANGENTS", "VERTEX_COLORS" ],
+];
+
+/// Debug modes, every one is tested
+const DEBUG_MODES : [ OutlineDebugMode; 6 ] = [
+    OutlineDebugMode::Off,
+    OutlineDebugMode::Fresnel,
+    OutlineDebugMode::NormalDotView,
+    OutlineDebugMode::Normals,
+    OutlineDebugMode::Power,
+    OutlineDebugMode::Coverage,
+];
+
+/// Shaders loaded by Bevy and outline plugins in headless app
+struct Shaders
+{
+    by_id : HashMap< AssetId< Shader >, Shader >,
+    by_import : HashMap< ShaderImport, Shader >,
+}
+
+impl Shaders
+{
+    /// Loads shaders of all outline plugins
+    fn load( ) -> Self
+    {
+        let mut app = App::new( );
+        app.add_plugins( DefaultPlugins
+            .set( RenderPlugin
+            {
+                render_creation : RenderCreation::Automatic( WgpuSettings { backends : None, ..default( ) } ),
+                ..default( )
+            })
+            .disable::< WinitPlugin >( )
+            .disable::< LogPlugin >( ) )
+            .add_plugins( (
+                OutlinePlugin::< RimEffect >::default( ),
+                OutlinePlugin::< RimEffectHook >::default( ),
+                OutlinePlugin2d::< RimEffect >::default( ),
+                OutlineXRayPlugin,
+            ));
+        // some Bevy shader modules are loaded on finish
+        app.finish( );
+        app.cleanup( );
+
+        let shaders = app.world.resource::< Assets< Shader > >( );
+        Self
+        {
+            by_id : shaders.iter( ).map( | ( id, shader ) | ( id, shader.clone( ) ) ).collect( ),
+            by_import : shaders.iter( ).map( | ( _, shader ) | ( shader.import_path.clone( ), shader.clone( ) ) ).collect( ),
+        }
+    }
+
+    /// Composes `shader` with `shader_defs` and validates result
+    fn validate( &self, shader : &Shader, shader_defs : &[ &str ] ) -> Result< (), String >
+    {
+        let module = self.compose( shader, shader_defs )?;
+        Validator::new( ValidationFlags::all( ), Capabilities::all( ) )
+            .validate( &module )
+            .map_err( | error | format!( "{error:?}" ) )?;
+        Ok( () )
+    }
+
+    /// Composes `shader` with `shader_defs` into naga module
+    fn compose( &self, shader : &Shader, shader_defs : &[ &str ] ) -> Result< Module, String >
+    {
+        let mut composer = Composer::default( ).with_capabilities( Capabilities::all( ) );
+        for import in &shader.imports {
+            self.add_import( &mut composer, import )?;
+        }
+        let mut defs : std::collections::HashMap< String, ShaderDefValue > = PIPELINE_SHADER_DEFS.iter( )
+            .map( | ( name, value ) | ( name.to_string( ), *value ) )
+            .collect( );
+        defs.extend( shader_defs.iter( ).map( | name | ( name.to_string( ), ShaderDefValue::Bool( true ) ) ) );
+        composer.make_naga_module( NagaModuleDescriptor { shader_defs : defs, ..shader.into( ) } )
+            .map_err( | error | error.emit_to_string( &composer ) )
+    }
+
+    /// Adds module of `import` and its dependencies to `composer`, as pipeline cache does
+    fn add_import( &self, composer : &mut Composer, import : &ShaderImport ) -> Result< (), String >
+    {
+        if composer.contains_module( &import.module_name( ) ) {
+            return Ok( () );
+        }
+        // missing module is reported by composer
+        let Some( shader ) = self.by_import.get( import ) else {
+            return Ok( () );
+        };
+        for import in &shader.imports {
+            self.add_import( composer, import )?;
+        }
+        if let Err( error ) = composer.add_composable_module( shader.into( ) ) {
+            return Err( error.emit_to_string( composer ) );
+        }
+        Ok( () )
+    }
+
+    /// Validates `shader` with every combination of `variants` and outline shader defs, returns failures
+    fn validate_variants( &self, shader : &Shader, variants : &[ Vec< &str > ] ) -> Vec< String >
+    {
+        let mut failures = Vec::new( );
+        for variant in variants {
+            for outline_defs in outline_shader_defs( ) {
+                let defs : Vec< &str > = variant.iter( ).chain( outline_defs.iter( ) ).copied( ).collect( );
+                if let Err( error ) = self.validate( shader, &defs ) {
+                    failures.push( format!( "{} {defs:?}:\n{error}", shader.path ) );
+                }
+            }
+        }
+        failures
+    }
+
+    /// Returns shader loaded by plugins
+    fn get( &self, handle : &Handle< Shader > ) -> &Shader
+    {
+        self.by_id.get( &handle.id( ) ).expect( "shader is loaded" )
+    }
+}
+
+/// Shaders of all outline plugins, loaded by first test that needs them
+fn shaders( ) -> &'static Shaders
+{
+    static SHADERS : OnceLock< Shaders > = OnceLock::new( );
+    SHADERS.get_or_init( Shaders::load )
+}
+
+/// Every combination of quality, silhouette and debug mode shader defs
+fn outline_shader_defs( ) -> Vec< Vec< &'static str > >
+{
+    let mut combinations = Vec::new( );
+    for low_quality in [ false, true ] {
+        for silhouette in [ false, true ] {
+            for debug in DEBUG_MODES {
+                let mut defs = Vec::new( );
+                if low_quality {
+                    defs.push( QUALITY_LOW_SHADER_DEF );
+                }
+                if silhouette {
+                    defs.push( SILHOUETTE_SHADER_DEF );
+                }
+                if let Some( def ) = debug.shader_def( ) {
+                    defs.extend( [ DEBUG_SHADER_DEF, def ] );
+                }
+                combinations.push( defs );
+            }
+        }
+    }
+    combinations
+}
+
+/// Every combination of view and mesh shader defs of 3D pipelines
+fn mesh_variants( ) -> Vec< Vec< &'static str > >
+{
+    VIEW_SHADER_DEFS.iter( )
+        .flat_map( | view | MESH_SHADER_DEFS.iter( ).map( | mesh | view.iter( ).chain( mesh.iter( ) ).copied( ).collect( ) ) )
+        .collect( )
+}
+
+/// Value of evaluated shader expression, scalars are one component vectors
+#[ derive( Debug, Clone, PartialEq ) ]
+enum Value
+{
+    Float( Vec< f32 > ),
+    Uint( u32 ),
+    Bool( bool ),
+    Local( naga::Handle< naga::LocalVariable > ),
+}
+
+impl Value
+{
+    /// Returns components of float value
+    fn floats( &self ) -> &[ f32 ]
+    {
+        match self {
+            Self::Float( components ) => components,
+            other => panic!( "{other:?} is not float" ),
+        }
+    }
+
+    /// Returns scalar float
+    fn float( &self ) -> f32
+    {
+        self.floats( )[ 0 ]
+    }
+}
+
+/// Evaluator of straight-line shader functions: arguments, literals, local variables, arithmetic,
+/// comparisons, `if` and few math functions. Other IR panics, so shader change that needs more fails loudly.
+struct Evaluator< 'a >
+{
+    function : &'a naga::Function,
+    arguments : Vec< Value >,
+    values : HashMap< naga::Handle< Expression >, Value >,
+    locals : HashMap< naga::Handle< naga::LocalVariable >, Value >,
+}
+
+impl< 'a > Evaluator< 'a >
+{
+    /// Calls function of `module` whose name before `naga_oil` mangling is `name`
+    fn call( module : &'a Module, name : &str, arguments : Vec< Value > ) -> Value
+    {
+        let function = module.functions.iter( )
+            .map( | ( _, function ) | function )
+            .find( | function | function.name.as_deref( ).and_then( | mangled | mangled.split( "X_naga_oil" ).next( ) ) == Some( name ) )
+            .unwrap_or_else( || panic!( "function {name} is composed" ) );
+        let mut evaluator = Self { function, arguments, values : HashMap::default( ), locals : HashMap::default( ) };
+        evaluator.block( &function.body ).unwrap_or_else( || panic!( "{name} returns value" ) )
+    }
+
+    /// Runs statements of `block`, returns returned value
+    fn block( &mut self, block : &naga::Block ) -> Option< Value >
+    {
+        for statement in block.iter( ) {
+            match statement {
+                Statement::Emit( range ) => {
+                    for handle in range.clone( ) {
+                        let value = self.evaluate( handle );
+                        self.values.insert( handle, value );
+                    }
+                }
+                Statement::Store { pointer, value } => {
+                    let Value::Local( local ) = self.value( *pointer ) else {
+                        panic!( "store into local variable only" );
+                    };
+                    let value = self.value( *value );
+                    self.locals.insert( local, value );
+                }
+                Statement::If { condition, accept, reject } => {
+                    let branch = if self.value( *condition ) == Value::Bool( true ) { accept } else { reject };
+                    if let Some( value ) = self.block( branch ) {
+                        return Some( value );
+                    }
+                }
+                Statement::Block( block ) => {
+                    if let Some( value ) = self.block( block ) {
+                        return Some( value );
+                    }
+                }
+                Statement::Return { value } => return value.map( | value | self.value( value ) ),
+                other => panic!( "unsupported statement {other:?}" ),
+            }
+        }
+        None
+    }
+
+    /// Returns emitted value of `handle` or evaluates expression that needs no emit
+    fn value( &mut self, handle : naga::Handle< Expression > ) -> Value
+    {
+        match self.values.get( &handle ) {
+            Some( value ) => value.clone( ),
+            None => self.evaluate( handle ),
+        }
+    }
+
+    /// Evaluates expression `handle`
+    fn evaluate( &mut self, handle : naga::Handle< Expression > ) -> Value
+    {
+        match &self.function.expressions[ handle ] {
+            Expression::FunctionArgument( index ) => self.arguments[ *index as usize ].clone( ),
+            Expression::Literal( Literal::F32( value ) ) => Value::Float( vec![ *value ] ),
+            Expression::Literal( Literal::U32( value ) ) => Value::Uint( *value ),
+            Expression::Literal( Literal::Bool( value ) ) => Value::Bool( *value ),
+            Expression::LocalVariable( local ) => Value::Local( *local ),
+            Expression::Load { pointer } => {
+                let Value::Local( local ) = self.value( *pointer ) else {
+                    panic!( "load from local variable only" );
+                };
+                self.locals.get( &local ).cloned( ).expect( "local variable is stored before load" )
+            }
+            Expression::Binary { op, left, right } => {
+                let ( left, right ) = ( self.value( *left ), self.value( *right ) );
+                binary( *op, &left, &right )
+            }
+            Expression::Math { fun, arg, arg1, .. } => {
+                let arg = self.value( *arg );
+                let arg1 = arg1.map( | arg1 | self.value( arg1 ) );
+                math( *fun, &arg, arg1.as_ref( ) )
+            }
+            other => panic!( "unsupported expression {other:?}" ),
+        }
+    }
+}
+
+/// Applies binary operator, scalar float operand is broadcast to vector one
+fn binary( op : BinaryOperator, left : &Value, right : &Value ) -> Value
+{
+    if let ( Value::Uint( left ), Value::Uint( right ) ) = ( left, right ) {
+        return match op {
+            BinaryOperator::Less => Value::Bool( left < right ),
+            BinaryOperator::LessEqual => Value::Bool( left <= right ),
+            BinaryOperator::Greater => Value::Bool( left > right ),
+            BinaryOperator::GreaterEqual => Value::Bool( left >= right ),
+            BinaryOperator::Equal => Value::Bool( left == right ),
+            BinaryOperator::NotEqual => Value::Bool( left != right ),
+            other => panic!( "unsupported u32 operator {other:?}" ),
+        };
+    }
+    let ( left, right ) = ( left.floats( ), right.floats( ) );
+    let count = left.len( ).max( right.len( ) );
+    let component = | values : &[ f32 ], index : usize | values[ index.min( values.len( ) - 1 ) ];
+    let compare = | test : fn( f32, f32 ) -> bool | Value::Bool( test( left[ 0 ], right[ 0 ] ) );
+    let arithmetic = | apply : fn( f32, f32 ) -> f32 | {
+        Value::Float( ( 0..count ).map( | index | apply( component( left, index ), component( right, index ) ) ).collect( ) )
+    };
+    match op {
+        BinaryOperator::Add => arithmetic( | a, b | a + b ),
+        BinaryOperator::Subtract => arithmetic( | a, b | a - b ),
+        BinaryOperator::Multiply => arithmetic( | a, b | a * b ),
+        BinaryOperator::Divide => arithmetic( | a, b | a / b ),
+        BinaryOperator::Less => compare( | a, b | a < b ),
+        BinaryOperator::LessEqual => compare( | a, b | a <= b ),
+        BinaryOperator::Greater => compare( | a, b | a > b ),
+        BinaryOperator::GreaterEqual => compare( | a, b | a >= b ),
+        other => panic!( "unsupported f32 operator {other:?}" ),
+    }
+}
+
+/// Applies math function to float arguments
+fn math( fun : MathFunction, arg : &Value, arg1 : Option< &Value > ) -> Value
+{
+    let unary = | apply : fn( f32 ) -> f32 | Value::Float( arg.floats( ).iter( ).map( | value | apply( *value ) ).collect( ) );
+    let second = | | arg1.expect( "function has second argument" ).floats( );
+    match fun {
+        MathFunction::Sin => unary( f32::sin ),
+        MathFunction::Cos => unary( f32::cos ),
+        MathFunction::Abs => unary( f32::abs ),
+        MathFunction::Saturate => unary( | value | value.clamp( 0.0, 1.0 ) ),
+        MathFunction::Max => Value::Float( arg.floats( ).iter( ).zip( second( ).iter( ).cycle( ) ).map( | ( a, b ) | a.max( *b ) ).collect( ) ),
+        MathFunction::Min => Value::Float( arg.floats( ).iter( ).zip( second( ).iter( ).cycle( ) ).map( | ( a, b ) | a.min( *b ) ).collect( ) ),
+        MathFunction::Pow => Value::Float( vec![ arg.float( ).powf( second( )[ 0 ] ) ] ),
+        MathFunction::Dot => Value::Float( vec![ arg.floats( ).iter( ).zip( second( ) ).map( | ( a, b ) | a * b ).sum( ) ] ),
+        other => panic!( "unsupported math function {other:?}" ),
+    }
+}
+
+/// Panics with all `failures`
+fn assert_valid( failures : Vec< String > )
+{
+    assert!( failures.is_empty( ), "{} invalid shader variants:\n\n{}", failures.len( ), failures.join( "\n\n" ) );
+}
+
+#[ test ]
+fn rim_effect_shader_is_valid( )
+{
+    let shaders = shaders( );
+    assert_valid( shaders.validate_variants( shaders.get( &SHADER_HANDLE ), &mesh_variants( ) ) );
+}
+
+#[ test ]
+fn rim_effect_hook_is_valid( )
+{
+    let shaders = shaders( );
+    let hook = Shader::from_wgsl( HOOK_SHADER, "hook.wgsl" );
+    let variants : Vec< Vec< &str > > = mesh_variants( ).into_iter( )
+        .map( | mut defs | {
+            defs.push( HOOK_SHADER_DEF );
+            defs
+        })
+        .collect( );
+    assert_valid( shaders.validate_variants( &hook, &variants ) );
+}
+
+#[ test ]
+fn rim_effect_2d_shader_is_valid( )
+{
+    let shaders = shaders( );
+    let variants = vec![
+        vec![ ],
+        vec![ "VERTEX_COLORS" ],
+        vec![ "TONEMAP_IN_SHADER", "TONEMAP_METHOD_TONY_MC_MAPFACE", "DEBAND_DITHER" ],
+    ];
+    assert_valid( shaders.validate_variants( shaders.get( &SHADER_2D_HANDLE ), &variants ) );
+}
+
+#[ test ]
+fn xray_shader_is_valid( )
+{
+    let shaders = shaders( );
+    let failures : Vec< String > = mesh_variants( ).iter( )
+        .filter_map( | defs | shaders.validate( shaders.get( &XRAY_SHADER_HANDLE ), defs ).err( ) )
+        .collect( );
+    assert_valid( failures );
+}
+
+#[ test ]
+fn rim_math_matches_rust_mirror( )
+{
+    let shaders = shaders( );
+    let module = shaders.compose( &Shader::from_wgsl( RIM_MATH_SHADER, "rim_math.wgsl" ), &[ ] ).unwrap( );
+    let mut failures = Vec::new( );
+    for width in [ 0.5, 1.0, 2.0, 3.0 ] {
+        for time in [ 0.0, 1.0, std::f32::consts::FRAC_PI_2, -std::f32::consts::FRAC_PI_2, 4.0 ] {
+            for is_time_related in [ 0, 1 ] {
+                let arguments = vec![ Value::Float( vec![ width ] ), Value::Float( vec![ time ] ), Value::Uint( is_time_related ) ];
+                let power = Evaluator::call( &module, "rim_power", arguments ).float( );
+                let expected = rim_power( width, time, is_time_related );
+                if ( power - expected ).abs( ) > 1e-5 {
+                    failures.push( format!( "rim_power( {width}, {time}, {is_time_related} ): shader {power}, Rust {expected}" ) );
+                }
+                for cos in [ -0.5_f32, 0.0, 0.2, 0.5, 0.75, 1.0 ] {
+                    let normal = Vec3::new( ( 1.0 - cos * cos ).sqrt( ), 0.0, cos );
+                    let arguments = vec![ Value::Float( normal.to_array( ).to_vec( ) ), Value::Float( vec![ 0.0, 0.0, 1.0 ] ), Value::Float( vec![ power ] ) ];
+                    let level = Evaluator::call( &module, "rim_level", arguments ).float( );
+                    let expected = rim_level( normal, Vec3::Z, power );
+                    if ( level - expected ).abs( ) > 1e-5 {
+                        failures.push( format!( "rim_level( N·V {cos}, power {power} ): shader {level}, Rust {expected}" ) );
+                    }
+                }
+            }
+        }
+    }
+    assert!( failures.is_empty( ), "Rust mirror differs from shader:\n{}", failures.join( "\n" ) );
+}
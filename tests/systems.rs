@@ -0,0 +1,325 @@
+//! Outline systems in headless app, time is advanced by hand so results are deterministic
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use outlines::base::OutlineMaterial;
+use outlines::commands::OutlineCommandsExt;
+use outlines::outline::Outline;
+use outlines::pending::OutlinePending;
+use outlines::picking::{OutlineHoverExit, OutlineHovered, OutlinePickingPlugin};
+use outlines::rim_effect::RimEffect;
+use outlines::rules::OutlineRules;
+use outlines::settings::OutlineSettings;
+use outlines::silhouette::OutlineSilhouette;
+use outlines::state::{OutlineState, OutlineStatePresets};
+use outlines::sync::{OutlineSync, OutlineSyncGroups};
+use outlines::transition::OutlineTransition;
+use outlines::testing::{outline_test_app, OutlineTestExt};
+
+/// Default outline with `time_scale`
+fn outline( time_scale : f32 ) -> Outline< RimEffect >
+{
+    let mut outline = Outline::< RimEffect >::default( );
+    outline.time_scale = time_scale;
+    outline
+}
+
+#[ test ]
+fn insert_outline_converts_material_and_remove_restores_it( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    let entity = app.spawn_base( StandardMaterial::default( ) );
+    let base = app.world.get::< Handle< StandardMaterial > >( entity ).cloned( ).unwrap( );
+
+    app.run_commands( | mut commands | {
+        commands.entity( entity ).insert_outline( outline( 1.0 ) );
+    });
+    assert!( app.world.get::< Handle< StandardMaterial > >( entity ).is_none( ) );
+    assert!( app.world.get::< RimEffect >( entity ).is_some( ) );
+    assert_eq!( app.outline::< RimEffect >( entity ).map( | outline | outline.width ), Some( outline( 1.0 ).width ) );
+
+    app.run_commands( | mut commands | {
+        commands.entity( entity ).remove_outline::< RimEffect >( );
+    });
+    assert_eq!( app.world.get::< Handle< StandardMaterial > >( entity ).map( Handle::id ), Some( base.id( ) ) );
+    assert!( app.world.get::< RimEffect >( entity ).is_none( ) );
+    assert!( app.outline::< RimEffect >( entity ).is_none( ) );
+}
+
+#[ test ]
+fn material_edited_in_place_is_not_reused_from_cache( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    let base = app.world.resource_mut::< Assets< StandardMaterial > >( ).add( StandardMaterial::default( ) );
+    let edited = app.world.spawn( ( base.clone( ), TransformBundle::default( ) ) ).id( );
+    let later = app.world.spawn( ( base, TransformBundle::default( ) ) ).id( );
+    app.run_commands( | mut commands | {
+        commands.entity( edited ).insert_outline( outline( 1.0 ) );
+    });
+    let handle = app.world.get::< Handle< OutlineMaterial< RimEffect > > >( edited ).cloned( ).unwrap( );
+    app.world.resource_mut::< Assets< OutlineMaterial< RimEffect > > >( ).get_mut( &handle ).unwrap( ).extension.width = 7.0;
+
+    app.run_commands( | mut commands | {
+        commands.entity( later ).insert_outline( outline( 1.0 ) );
+    });
+    assert_eq!( app.outline::< RimEffect >( later ).map( | outline | outline.width ), Some( outline( 1.0 ).width ) );
+    assert_eq!( app.outline::< RimEffect >( edited ).map( | outline | outline.width ), Some( 7.0 ) );
+}
+
+#[ test ]
+fn remove_after_transition_restores_original_base( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    let entity = app.spawn_base( StandardMaterial::default( ) );
+    let base = app.world.get::< Handle< StandardMaterial > >( entity ).cloned( ).unwrap( );
+    let bases = app.world.resource::< Assets< StandardMaterial > >( ).len( );
+    app.run_commands( | mut commands | {
+        commands.entity( entity )
+            .insert_outline( outline( 1.0 ) )
+            .insert( OutlineTransition::< RimEffect >::color( Color::RED, 0.1 ) );
+    });
+    let cached = app.world.get::< Handle< OutlineMaterial< RimEffect > > >( entity ).map( Handle::id );
+
+    app.step( 0.05 );
+    assert_ne!( app.world.get::< Handle< OutlineMaterial< RimEffect > > >( entity ).map( Handle::id ), cached, "transition copies material" );
+
+    app.run_commands( | mut commands | {
+        commands.entity( entity ).remove_outline::< RimEffect >( );
+    });
+    assert_eq!( app.world.get::< Handle< StandardMaterial > >( entity ).map( Handle::id ), Some( base.id( ) ) );
+    assert_eq!( app.world.resource::< Assets< StandardMaterial > >( ).len( ), bases, "no base copy is added" );
+}
+
+#[ test ]
+fn outline_time_accumulates_with_time_scale_and_wraps( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    let entity = app.spawn_base( StandardMaterial::default( ) );
+    app.run_commands( | mut commands | {
+        commands.entity( entity ).insert_outline( outline( 2.0 ) );
+    });
+
+    app.step( 0.25 );
+    let time = app.outline::< RimEffect >( entity ).unwrap( ).u_time;
+    assert!( ( time - 0.5 ).abs( ) < 1e-5, "{time}" );
+
+    app.step( 0.0 );
+    assert_eq!( app.outline::< RimEffect >( entity ).unwrap( ).u_time, time );
+
+    app.step( 4.0 );
+    let time = app.outline::< RimEffect >( entity ).unwrap( ).u_time;
+    let expected = ( 0.5 + 8.0 ) % TAU;
+    assert!( ( time - expected ).abs( ) < 1e-4, "{time} != {expected}" );
+}
+
+#[ test ]
+fn synced_outline_does_not_change_time_of_unsynced_one_on_same_base( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    let base = app.world.resource_mut::< Assets< StandardMaterial > >( ).add( StandardMaterial::default( ) );
+    let synced = app.world.spawn( ( base.clone( ), TransformBundle::default( ) ) ).id( );
+    let unsynced = app.world.spawn( ( base, TransformBundle::default( ) ) ).id( );
+    app.run_commands( | mut commands | {
+        commands.entity( synced ).insert_outline( outline( 1.0 ) ).insert( OutlineSync::new( 1 ) );
+        commands.entity( unsynced ).insert_outline( outline( 1.0 ) );
+    });
+
+    app.step( 0.5 );
+    app.world.resource_mut::< OutlineSyncGroups >( ).set_time( 1, 2.0 );
+    app.step( 0.0 );
+    let synced_time = app.outline::< RimEffect >( synced ).unwrap( ).u_time;
+    let unsynced_time = app.outline::< RimEffect >( unsynced ).unwrap( ).u_time;
+    assert!( ( synced_time - 2.0 ).abs( ) < 1e-5, "{synced_time}" );
+    assert!( ( unsynced_time - 0.5 ).abs( ) < 1e-5, "{unsynced_time}" );
+}
+
+#[ test ]
+fn disabled_outlines_are_not_animated( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    let entity = app.spawn_base( StandardMaterial::default( ) );
+    app.run_commands( | mut commands | {
+        commands.entity( entity )
+            .insert_outline( outline( 1.0 ) )
+            .insert( OutlineTransition::< RimEffect >::fade_out( 0.1 ) );
+    });
+    app.insert_resource( OutlineSettings::off( ) );
+
+    app.step( 0.05 );
+    let disabled = app.outline::< RimEffect >( entity ).unwrap( );
+    assert_eq!( ( disabled.u_time, disabled.intensity ), ( 0.0, 1.0 ) );
+
+    // transition starts in first enabled frame
+    app.insert_resource( OutlineSettings::default( ) );
+    app.step( 0.05 ).step( 0.05 );
+    let enabled = app.outline::< RimEffect >( entity ).unwrap( );
+    assert!( enabled.u_time > 0.0 && enabled.intensity < 1.0 );
+}
+
+#[ test ]
+fn state_change_moves_outline_to_preset_after_debounce( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    let entity = app.spawn_base( StandardMaterial::default( ) );
+    app.run_commands( | mut commands | {
+        commands.entity( entity )
+            .insert_outline( outline( 1.0 ) )
+            .insert( ( OutlineState::Idle, OutlineStatePresets::< RimEffect >::default( ).with_debounce( 0.05 ) ) );
+    });
+
+    // First state is applied at once
+    app.step( 0.0 );
+    assert_eq!( app.outline::< RimEffect >( entity ).unwrap( ).intensity, 0.0 );
+
+    *app.world.get_mut::< OutlineState >( entity ).unwrap( ) = OutlineState::Hovered;
+    app.step( 0.04 );
+    assert_eq!( app.outline::< RimEffect >( entity ).unwrap( ).intensity, 0.0, "state is debounced" );
+
+    app.step( 0.06 );
+    let intensity = app.outline::< RimEffect >( entity ).unwrap( ).intensity;
+    assert!( intensity > 0.0 && intensity < 1.0, "outline is moving to preset: {intensity}" );
+    let presets = app.world.get::< OutlineStatePresets< RimEffect > >( entity ).unwrap( );
+    assert_eq!( presets.applied( ), Some( OutlineState::Hovered ) );
+
+    app.step( 0.15 );
+    assert_eq!( app.outline::< RimEffect >( entity ).unwrap( ).intensity, 1.0 );
+}
+
+#[ test ]
+fn rules_outline_matching_entities( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    let mut door_outline = outline( 1.0 );
+    door_outline.width = 4.0;
+    app.insert_resource( OutlineRules::< RimEffect >::default( )
+        .exclude_name( "Door_Hitbox" )
+        .name( "Door_*", door_outline ) );
+    let door = app.spawn_base( StandardMaterial::default( ) );
+    let hitbox = app.spawn_base( StandardMaterial::default( ) );
+    let wall = app.spawn_base( StandardMaterial::default( ) );
+    app.world.entity_mut( door ).insert( Name::new( "Door_Front" ) );
+    app.world.entity_mut( hitbox ).insert( Name::new( "Door_Hitbox" ) );
+    app.world.entity_mut( wall ).insert( Name::new( "Wall" ) );
+
+    app.step( 0.0 );
+    assert_eq!( app.outline::< RimEffect >( door ).map( | outline | outline.width ), Some( 4.0 ) );
+    assert!( app.outline::< RimEffect >( hitbox ).is_none( ) );
+    assert!( app.outline::< RimEffect >( wall ).is_none( ) );
+}
+
+/// Marker of rule component test
+#[ derive( Component ) ]
+struct Interactable;
+
+#[ test ]
+fn component_rule_outlines_entity_that_gets_component_later( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    app.insert_resource( OutlineRules::< RimEffect >::default( ).component::< Interactable >( outline( 1.0 ) ) );
+    let entity = app.spawn_base( StandardMaterial::default( ) );
+
+    app.step( 0.0 );
+    assert!( app.outline::< RimEffect >( entity ).is_none( ) );
+
+    app.world.entity_mut( entity ).insert( Interactable );
+    app.step( 0.0 );
+    assert!( app.outline::< RimEffect >( entity ).is_some( ) );
+}
+
+#[ test ]
+fn outline_of_loading_material_is_applied_after_loading( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    let material = app.world.resource::< Assets< StandardMaterial > >( ).reserve_handle( );
+    let entity = app.world.spawn( ( material.clone( ), TransformBundle::default( ) ) ).id( );
+    app.run_commands( | mut commands | {
+        commands.entity( entity ).insert_outline( outline( 1.0 ) );
+    });
+
+    app.step( 0.0 );
+    assert!( app.world.get::< OutlinePending< RimEffect > >( entity ).is_some( ) );
+    assert!( app.outline::< RimEffect >( entity ).is_none( ) );
+
+    app.world.resource_mut::< Assets< StandardMaterial > >( ).insert( &material, StandardMaterial::default( ) );
+    // Asset events are sent at the end of frame, so outline is applied in next frame
+    app.step( 0.0 ).step( 0.0 );
+    assert!( app.world.get::< OutlinePending< RimEffect > >( entity ).is_none( ) );
+    assert!( app.outline::< RimEffect >( entity ).is_some( ) );
+}
+
+#[ test ]
+fn despawned_hovered_entity_is_cleared( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    app.add_plugins( OutlinePickingPlugin::< RimEffect >::default( ) );
+    let entity = app.spawn_base( StandardMaterial::default( ) );
+    app.run_commands( | mut commands | {
+        commands.entity( entity ).insert_outline( outline( 1.0 ) );
+    });
+    app.world.resource_mut::< OutlineHovered< RimEffect > >( ).entity = Some( entity );
+
+    app.world.despawn( entity );
+    app.step( 0.0 );
+    assert_eq!( app.world.resource::< OutlineHovered< RimEffect > >( ).entity, None );
+    let exits : Vec< Entity > = app.world.resource_mut::< Events< OutlineHoverExit< RimEffect > > >( )
+        .drain( )
+        .map( | event | event.entity )
+        .collect( );
+    assert_eq!( exits, vec![ entity ] );
+}
+
+#[ test ]
+fn silhouette_group_shares_cached_material( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    let base = app.world.resource_mut::< Assets< StandardMaterial > >( ).add( StandardMaterial::default( ) );
+    let root = app.world.spawn( ( TransformBundle::default( ), OutlineSilhouette::default( ) ) ).id( );
+    let parts = [ 0, 1 ].map( | _ | app.world.spawn( ( base.clone( ), TransformBundle::default( ) ) ).set_parent( root ).id( ) );
+    let alone = app.world.spawn( ( base, TransformBundle::default( ) ) ).id( );
+    app.run_commands( | mut commands | {
+        for entity in parts.into_iter( ).chain( [ alone ] ) {
+            commands.entity( entity ).insert_outline( outline( 1.0 ) );
+        }
+    });
+    let material = | app : &App, entity | app.world.get::< Handle< OutlineMaterial< RimEffect > > >( entity ).map( Handle::id );
+    let ungrouped = material( &app, alone );
+
+    app.step( 0.0 );
+    assert_eq!( material( &app, parts[ 0 ] ), material( &app, parts[ 1 ] ) );
+    assert_ne!( material( &app, parts[ 0 ] ), ungrouped );
+    assert_eq!( material( &app, alone ), ungrouped );
+    let assets = app.world.resource::< Assets< OutlineMaterial< RimEffect > > >( ).len( );
+
+    app.step( 0.0 );
+    assert_eq!( app.world.resource::< Assets< OutlineMaterial< RimEffect > > >( ).len( ), assets, "materials are reused" );
+
+    app.world.entity_mut( parts[ 1 ] ).remove_parent( );
+    app.step( 0.0 );
+    assert_eq!( material( &app, parts[ 1 ] ), ungrouped, "entity that left group gets cached material back" );
+}
+
+#[ test ]
+fn removed_outline_of_propagated_descendant_stays_removed( )
+{
+    let mut app = outline_test_app::< RimEffect >( );
+    let root = app.spawn_base( StandardMaterial::default( ) );
+    let child = app.spawn_base( StandardMaterial::default( ) );
+    app.world.entity_mut( child ).set_parent( root );
+    app.run_commands( | mut commands | {
+        commands.entity( root ).propagate_outline( outline( 1.0 ) );
+    });
+    app.step( 0.0 ).step( 0.0 );
+    assert!( app.outline::< RimEffect >( child ).is_some( ) );
+
+    app.run_commands( | mut commands | {
+        commands.entity( child ).remove_outline::< RimEffect >( );
+    });
+    // new child reshapes hierarchy, so propagation walks it again
+    let weapon = app.spawn_base( StandardMaterial::default( ) );
+    app.world.entity_mut( weapon ).set_parent( root );
+    app.step( 0.0 ).step( 0.0 );
+    assert!( app.outline::< RimEffect >( child ).is_none( ) );
+    assert!( app.outline::< RimEffect >( weapon ).is_some( ) );
+}